@@ -0,0 +1,27 @@
+#![feature(test)]
+
+extern crate test;
+
+use ghost_gc::{once_arena, Gc};
+use test::{black_box, Bencher};
+
+const OBJECTS: u64 = 100_000;
+
+#[bench]
+fn alloc_loop(b: &mut Bencher) {
+    b.iter(|| {
+        once_arena(|mt| {
+            let objects: Vec<_> = (0..OBJECTS).map(|i| Gc::new(i, mt)).collect();
+            black_box(objects);
+        })
+    });
+}
+
+#[bench]
+fn alloc_batch(b: &mut Bencher) {
+    b.iter(|| {
+        once_arena(|mt| {
+            black_box(mt.alloc_batch(0..OBJECTS));
+        })
+    });
+}
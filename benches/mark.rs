@@ -0,0 +1,50 @@
+#![feature(test)]
+
+extern crate test;
+
+use std::collections::VecDeque;
+
+use ghost_gc::{Arena, Gc, Rootable};
+use test::Bencher;
+
+const ELEMENTS: usize = 1_000_000;
+
+struct BytesRoot;
+
+impl Rootable for BytesRoot {
+    type Root<'l> = Gc<'l, (Gc<'l, u32>, Vec<u8>)>;
+}
+
+#[bench]
+fn mark_leaf_vec(b: &mut Bencher) {
+    let mut arena = Arena::<BytesRoot>::new(|mt| Gc::new((Gc::new(0, mt), vec![0; ELEMENTS]), mt));
+    b.iter(|| arena.complete_collection());
+}
+
+struct DequeRoot;
+
+impl Rootable for DequeRoot {
+    type Root<'l> = Gc<'l, (Gc<'l, u32>, VecDeque<u64>)>;
+}
+
+#[bench]
+fn mark_leaf_deque(b: &mut Bencher) {
+    let mut arena =
+        Arena::<DequeRoot>::new(|mt| Gc::new((Gc::new(0, mt), (0..ELEMENTS as u64).collect()), mt));
+    b.iter(|| arena.complete_collection());
+}
+
+struct ArrayRoot;
+
+impl Rootable for ArrayRoot {
+    type Root<'l> = Gc<'l, (Gc<'l, u32>, Box<[u64; ELEMENTS]>)>;
+}
+
+#[bench]
+fn mark_leaf_array(b: &mut Bencher) {
+    let mut arena = Arena::<ArrayRoot>::new(|mt| {
+        let array = vec![0; ELEMENTS].into_boxed_slice().try_into().unwrap();
+        Gc::new((Gc::new(0, mt), array), mt)
+    });
+    b.iter(|| arena.complete_collection());
+}
@@ -1,18 +1,44 @@
-use std::alloc::{Allocator, Global};
+use std::{
+    alloc::{Allocator, Global},
+    collections::HashMap,
+};
 
 use crate::{
     context::{Context, Pacing},
-    Collect, Mutation,
+    deep_clone::DeepCloner,
+    Collect, DeepClone, Mutation, Retain, Rooted, UniqueGc,
 };
-use alloc::boxed::Box;
+use alloc::{boxed::Box, rc::Weak};
 
 /// A garbage collected arena, inside which garbage collected pointers can be allocated.
+///
+/// # Thread safety
+/// An arena is neither [`Send`] nor [`Sync`], even if its root is. Any [`Collect`] type can be
+/// allocated inside it, including garbage which isn't reachable from the root, and which is
+/// dropped by whichever thread next collects. [`Rooted`] handles also share non-atomic
+/// reference counts with the arena. The handles aren't `Send`, and they stay on the thread
+/// which created them; if the arena were `Send`, it could be moved away from them, and both
+/// threads would update the same counts.
+///
+/// ```compile_fail
+/// # use ghost_gc::{Arena, Collect, Collector, Rootable};
+/// struct Empty;
+/// # unsafe impl Collect for Empty {
+/// #     const NEEDS_TRACE: bool = false;
+/// #     fn trace(&self, _c: &Collector) {}
+/// # }
+/// # impl Rootable for Empty { type Root<'l> = Empty; }
+///
+/// let a = Arena::<Empty>::new(|_| Empty);
+/// std::thread::spawn(move || a.allocations());
+/// ```
 pub struct Arena<R: Rootable, A = Global>
 where
     A: Allocator,
 {
-    context: Box<Context<A>>,
+    // The root is dropped first, as its destructor may still use the objects in the context.
     root: R::Root<'static>,
+    context: Box<Context<A>>,
 }
 
 impl<R> Arena<R>
@@ -26,6 +52,8 @@ where
         Arena::new_in(f, Global)
     }
 
+    /// # Panics
+    /// If the pacing has no trigger, without being [`Pacing::manual`].
     pub fn new_paced<F>(f: F, pacing: Pacing) -> Arena<R>
     where
         F: for<'b> FnOnce(&Mutation<'b>) -> R::Root<'b>,
@@ -47,12 +75,33 @@ where
         Arena::new_paced_in(f, Pacing::default(), alloc)
     }
 
+    /// # Panics
+    /// If the pacing has no trigger, without being [`Pacing::manual`].
     pub fn new_paced_in<F>(f: F, pacing: Pacing, alloc: A) -> Arena<R, A>
     where
         F: for<'b> FnOnce(&Mutation<'b>) -> R::Root<'b>,
         A: Allocator + 'static,
     {
-        let context: Box<Context<A>> = Box::new(Context::new_in(pacing, alloc));
+        Arena::with_capacity_in(f, pacing, alloc, 0)
+    }
+
+    /// Constructs a new arena with the given pacing and allocator, with space reserved to track
+    /// at least `object_capacity` allocations without reallocating.
+    ///
+    /// # Panics
+    /// If the pacing has no trigger, without being [`Pacing::manual`].
+    pub fn with_capacity_in<F>(
+        f: F,
+        pacing: Pacing,
+        alloc: A,
+        object_capacity: usize,
+    ) -> Arena<R, A>
+    where
+        F: for<'b> FnOnce(&Mutation<'b>) -> R::Root<'b>,
+        A: Allocator + 'static,
+    {
+        let context: Box<Context<A>> =
+            Box::new(Context::with_capacity_in(pacing, alloc, object_capacity));
         let root = f(Mutation::new(&context));
 
         Arena { context, root }
@@ -65,6 +114,52 @@ where
         f(&self.root, Mutation::new(&self.context))
     }
 
+    /// Calls the async closure as with [`Arena::view`], returning a future which can `.await`
+    /// other futures while holding garbage collected pointers.
+    ///
+    /// The future borrows the arena for as long as it exists, and collection needs a mutable
+    /// borrow, so no collection can run while the future is suspended, and every object stays
+    /// alive across each `.await`. Collection work only resumes once the future has completed or
+    /// been dropped, which means that a long running future should be split up into several
+    /// views, so that the arena can be collected between them.
+    ///
+    /// ```compile_fail
+    /// # use ghost_gc::{Arena, Gc, Rootable};
+    /// # struct Stack;
+    /// # impl Rootable for Stack { type Root<'l> = Vec<Gc<'l, u32>>; }
+    /// let mut arena = Arena::<Stack>::new(|_| Vec::new());
+    ///
+    /// let future = arena.view_async(async |_, mt| *Gc::new(1, mt));
+    /// arena.run_collection();
+    /// drop(future);
+    /// ```
+    pub async fn view_async<F, Ret>(&self, f: F) -> Ret
+    where
+        F: for<'b> AsyncFnOnce(&R::Root<'b>, &Mutation<'b>) -> Ret,
+    {
+        f(&self.root, Mutation::new(&self.context)).await
+    }
+
+    /// Calls the closure as with [`Arena::view`], additionally returning the number of objects
+    /// which were allocated during the closure.
+    pub fn view_counted<F, Ret>(&self, f: F) -> (Ret, usize)
+    where
+        F: for<'b> FnOnce(&R::Root<'b>, &Mutation<'b>) -> Ret,
+    {
+        // No objects can be freed during the closure, so this is exactly the number allocated.
+        let before = self.allocations();
+        let ret = self.view(f);
+
+        (ret, self.allocations() - before)
+    }
+
+    /// Calls the closure with a mutable reference to the root.
+    ///
+    /// The root is traced again before the current collection finishes, so objects stored into it
+    /// during the closure are kept alive without a write barrier. No collection work runs during
+    /// the closure itself, as that only happens in [`Arena::run_collection`] and its relatives,
+    /// so objects which are allocated but not yet stored anywhere are also valid until the closure
+    /// returns.
     pub fn view_mut<F, Ret>(&mut self, f: F) -> Ret
     where
         F: for<'b> FnOnce(&mut R::Root<'b>, &Mutation<'b>) -> Ret,
@@ -73,6 +168,115 @@ where
         f(&mut self.root, Mutation::new(&self.context))
     }
 
+    /// Calls the closure `n` times as with [`Arena::view_mut`], running a step of collection
+    /// after each call, as with [`Arena::run_collection`].
+    ///
+    /// This is the loop of an interpreter which is collected incrementally, such as one running
+    /// a fixed number of instructions for each frame.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{Arena, Gc, Rootable};
+    /// struct Stack;
+    ///
+    /// impl Rootable for Stack {
+    ///     type Root<'l> = Vec<Gc<'l, u32>>;
+    /// }
+    ///
+    /// let mut arena = Arena::<Stack>::new(|_| Vec::new());
+    /// arena.run_steps(100, |stack, mt| {
+    ///     let top = stack.pop().map_or(0, |top| *top);
+    ///     stack.push(Gc::new(top + 1, mt));
+    /// });
+    ///
+    /// arena.view(|stack, _| assert_eq!(*stack[0], 100));
+    /// ```
+    pub fn run_steps<F>(&mut self, n: usize, mut f: F)
+    where
+        F: for<'b> FnMut(&mut R::Root<'b>, &Mutation<'b>),
+    {
+        for _ in 0..n {
+            self.view_mut(|root, mt| f(root, mt));
+            self.run_collection();
+        }
+    }
+
+    /// Allocates `val` in the arena, and roots it until the returned handle is dropped.
+    ///
+    /// As the value is allocated outside of any [`Arena::view`], it cannot contain any garbage
+    /// collected pointers into this arena, but other objects may later point to it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{Arena, Collect, Collector, Rootable};
+    /// # struct Empty;
+    /// # unsafe impl Collect for Empty {
+    /// #     const NEEDS_TRACE: bool = false;
+    /// #     fn trace(&self, _c: &Collector) {}
+    /// # }
+    /// # impl Rootable for Empty { type Root<'l> = Empty; }
+    /// let mut arena = Arena::<Empty>::new(|_| Empty);
+    ///
+    /// let five = arena.alloc(5u32);
+    /// arena.complete_collection();
+    ///
+    /// arena.view(|_, mt| assert_eq!(*five.get(mt), 5));
+    /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn alloc<T: Collect + 'static>(&mut self, val: T) -> Rooted<T> {
+        let gc = UniqueGc::into_gc(UniqueGc::new(val, Mutation::new(&self.context)));
+
+        // The root set has to be traced again, in case the cycle is already marking.
+        self.context.set_root_untraced();
+
+        // Safety: The object was just allocated in this arena's context.
+        unsafe { self.context.explicit_roots().register(gc.into_box()) }
+    }
+
+    /// Runs the finalizer of every object which has been found to be unreachable, and was
+    /// allocated with [`Gc::new_finalized`].
+    ///
+    /// Finalized objects are freed by a later collection, once they are unreachable again.
+    ///
+    /// [`Gc::new_finalized`]: crate::Gc::new_finalized
+    pub fn run_finalizers(&mut self) {
+        self.context.run_finalizers(usize::MAX);
+    }
+
+    /// Runs the finalizers of at most `max` queued objects, in the order they were found to be
+    /// unreachable, returning how many were run.
+    ///
+    /// This allows the work of finalization to be spread out, as with incremental collection.
+    pub fn drain_finalizers(&mut self, max: usize) -> usize {
+        self.context.run_finalizers(max)
+    }
+
+    /// Returns the number of objects which are waiting for their finalizers to be run.
+    pub fn pending_finalizers(&self) -> usize {
+        self.context.pending_finalizers()
+    }
+
+    /// Returns `true` if calling [`Arena::run_collection`] would do any work, either because a
+    /// collection is already in progress, or because enough has been allocated to start one.
+    pub fn needs_collection(&self) -> bool {
+        self.context.needs_collection()
+    }
+
+    /// Removes every entry of the root for which `f` returns `false`, and then runs a full
+    /// collection to reclaim them.
+    ///
+    /// Objects are only ever freed once they can no longer be reached from the root, so removing
+    /// them from the root and then collecting, as this does, is how memory is given back. Objects
+    /// which are still reachable through another entry are kept alive.
+    pub fn retain_root<F>(&mut self, mut f: F)
+    where
+        for<'b> R::Root<'b>: Retain,
+        F: for<'b> FnMut(&<R::Root<'b> as Retain>::Key, &<R::Root<'b> as Retain>::Value) -> bool,
+    {
+        self.view_mut(|root, _| root.retain(&mut f));
+        self.complete_collection();
+    }
+
     pub fn run_collection(&mut self) {
         self.context.advance_collection(&self.root);
     }
@@ -81,9 +285,450 @@ where
         self.context.run_full_cycle(&self.root);
     }
 
+    /// Runs the collection one step at a time, as with [`Arena::run_collection`], until either
+    /// the current cycle finishes or `should_stop` returns `true`, returning whether the cycle
+    /// finished.
+    ///
+    /// `should_stop` is checked before every step, so that the caller can bound the time spent
+    /// collecting with any clock, and the amount of work done by each step is set by the
+    /// arena's [`Pacing`]. If no collection is in progress, or needs to be started, this does
+    /// nothing.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{Arena, Gc, Rootable};
+    /// # use std::time::{Duration, Instant};
+    /// # struct Values;
+    /// # impl Rootable for Values { type Root<'l> = Vec<Gc<'l, u32>>; }
+    /// let mut arena = Arena::<Values>::new(|mt| (0..1000).map(|i| Gc::new(i, mt)).collect());
+    ///
+    /// let deadline = Instant::now() + Duration::from_millis(2);
+    /// arena.collect_until(|| Instant::now() >= deadline);
+    /// ```
+    pub fn collect_until(&mut self, mut should_stop: impl FnMut() -> bool) -> bool {
+        let cycle = self.context.cursor().cycle;
+
+        while self.needs_collection() && self.context.cursor().cycle == cycle {
+            if should_stop() {
+                return false;
+            }
+
+            self.run_collection();
+        }
+
+        true
+    }
+
+    /// Returns the position of the collector within its current cycle, so that a scheduler can
+    /// decide how to interleave the rest of the cycle with other work.
+    ///
+    /// The collector's state is always kept by the arena itself, so the cursor never has to be
+    /// restored; [`Arena::run_collection`] always continues from where the last step left off.
+    /// The cursor identifies the cycle it was saved in, which [`Arena::resume_collection`] can
+    /// then finish.
+    pub fn save_collection_cursor(&self) -> CollectionCursor {
+        self.context.cursor()
+    }
+
+    /// Finishes the cycle the cursor was saved in, continuing from wherever the collector is,
+    /// without restarting the marking of a cycle which is already in progress.
+    ///
+    /// If the collector was asleep when the cursor was saved, this runs the whole of the next
+    /// cycle, and if the cycle has already finished, this does nothing.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{Arena, CursorPhase, Gc, Pacing, Rootable};
+    /// # struct Value;
+    /// # impl Rootable for Value { type Root<'l> = Gc<'l, u32>; }
+    /// let pacing = Pacing { mark_stride: 1, ..Pacing::default() };
+    /// let mut arena = Arena::<Value>::new_paced(|mt| Gc::new(5, mt), pacing);
+    ///
+    /// let cursor = arena.save_collection_cursor();
+    /// assert_eq!(cursor.phase, CursorPhase::Sleep);
+    ///
+    /// arena.resume_collection(cursor);
+    /// assert_eq!(arena.save_collection_cursor().cycle, cursor.cycle + 1);
+    /// ```
+    pub fn resume_collection(&mut self, cursor: CollectionCursor) {
+        self.context.finish_cycle(&self.root, cursor.cycle);
+    }
+
+    /// Runs a full collection as with [`Arena::complete_collection`], returning how much it
+    /// freed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{Arena, Collect, Collector, Gc, Rootable};
+    /// # struct Empty;
+    /// # unsafe impl Collect for Empty {
+    /// #     const NEEDS_TRACE: bool = false;
+    /// #     fn trace(&self, _c: &Collector) {}
+    /// # }
+    /// # impl Rootable for Empty { type Root<'l> = Empty; }
+    /// let mut arena = Arena::<Empty>::new(|_| Empty);
+    /// arena.view(|_, mt| {
+    ///     let _garbage = Gc::new([0u8; 1024], mt);
+    /// });
+    ///
+    /// let report = arena.complete_collection_reporting();
+    /// println!("freed {} objects, {} bytes", report.objects_freed, report.bytes_freed);
+    /// ```
+    pub fn complete_collection_reporting(&mut self) -> CollectionReport {
+        let (objects, bytes) = (self.allocations(), self.live_bytes());
+        self.complete_collection();
+
+        CollectionReport {
+            objects_freed: objects - self.allocations(),
+            bytes_freed: bytes - self.live_bytes(),
+            objects_surviving: self.allocations(),
+        }
+    }
+
     pub fn allocations(&self) -> usize {
         self.context.allocations()
     }
+
+    /// Returns the number of bytes allocated for every object in the arena, including the
+    /// header of each allocation.
+    pub fn live_bytes(&self) -> usize {
+        self.context.live_bytes()
+    }
+
+    /// Returns whether the collector may move objects, which it never does.
+    ///
+    /// Every object stays at the address it was allocated at until it is freed, which
+    /// [`ByAddress`], [`Gc::ptr_eq`], [`Gc::order_key`] and pointers handed out through
+    /// [`Gc::into_raw`] all rely on. As a consequence, the arena is never defragmented, and
+    /// memory freed by the sweep is only reused through the allocator.
+    ///
+    /// [`ByAddress`]: crate::ByAddress
+    /// [`Gc::ptr_eq`]: crate::Gc::ptr_eq
+    /// [`Gc::order_key`]: crate::Gc::order_key
+    /// [`Gc::into_raw`]: crate::Gc::into_raw
+    pub const fn is_compacting(&self) -> bool {
+        false
+    }
+
+    /// Registers a callback which is called with the address of each object whose value is
+    /// dropped because it was only reachable through [`Weak`] pointers, replacing any previous
+    /// callback.
+    ///
+    /// The address is the same as [`Gc::as_ptr`] returns for the object, which allows entries
+    /// for it to be removed from a cache without polling [`Weak::upgrade`]. The callback is
+    /// called as marking ends, and every later [`Weak::upgrade`] of the object returns `None`.
+    ///
+    /// [`Weak`]: crate::Weak
+    /// [`Weak::upgrade`]: crate::Weak::upgrade
+    /// [`Gc::as_ptr`]: crate::Gc::as_ptr
+    pub fn on_weak_cleared(&mut self, f: impl FnMut(*const ()) + 'static) {
+        self.context.set_on_weak_cleared(Box::new(f));
+    }
+
+    /// Records the current root and allocations, so that everything allocated afterwards can be
+    /// freed with [`Arena::rollback`].
+    ///
+    /// The root is copied, so it has to be a cheap handle such as a [`Gc`]. Only allocations are
+    /// undone by a rollback, so modifications made to existing objects since remain.
+    ///
+    /// [`Gc`]: crate::Gc
+    pub fn checkpoint(&self) -> Checkpoint<R>
+    where
+        R::Root<'static>: Copy,
+    {
+        Checkpoint {
+            root: self.root,
+            allocations: self.allocations(),
+            swept: self.context.swept(),
+            arena: self.context.explicit_roots().id(),
+        }
+    }
+
+    /// Restores the root recorded by the checkpoint, and frees every object allocated since it
+    /// was taken, without running their finalizers.
+    ///
+    /// Any collection which is in progress is abandoned, and starts over when next run.
+    ///
+    /// # Panics
+    /// - If the checkpoint was taken from a different arena.
+    /// - If a collection has freed any objects since the checkpoint was taken.
+    /// - If an object allocated since the checkpoint is still reachable, such as by being stored
+    ///   into an object which existed beforehand, or through a [`Rooted`] handle. Nothing is
+    ///   freed in this case, and the root is left as it was.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{Arena, Gc, Rootable};
+    /// struct Counter;
+    ///
+    /// impl Rootable for Counter {
+    ///     type Root<'l> = Gc<'l, u32>;
+    /// }
+    ///
+    /// let mut arena = Arena::<Counter>::new(|mt| Gc::new(0, mt));
+    /// let checkpoint = arena.checkpoint();
+    ///
+    /// arena.view_mut(|root, mt| *root = Gc::new(**root + 1, mt));
+    /// assert_eq!(arena.allocations(), 2);
+    ///
+    /// arena.rollback(checkpoint);
+    /// assert_eq!(arena.allocations(), 1);
+    /// arena.view(|root, _| assert_eq!(**root, 0));
+    /// ```
+    pub fn rollback(&mut self, checkpoint: Checkpoint<R>)
+    where
+        R::Root<'static>: Copy,
+    {
+        assert!(
+            self.context.explicit_roots().is_id(&checkpoint.arena),
+            "checkpoint was taken from a different arena"
+        );
+        assert_eq!(
+            self.context.swept(),
+            checkpoint.swept,
+            "objects have been collected since the checkpoint was taken"
+        );
+
+        // Safety: Nothing has been swept, so the objects allocated since the checkpoint are
+        // exactly those after the ones which existed when it was taken.
+        unsafe {
+            self.context
+                .truncate(&checkpoint.root, checkpoint.allocations)
+        };
+        self.root = checkpoint.root;
+    }
+
+    /// Returns statistics accumulated over every collection cycle so far.
+    pub fn collect_generation_stats(&self) -> GenerationStats {
+        self.context.generation_stats()
+    }
+
+    /// Resets the statistics returned by [`Arena::collect_generation_stats`], so that they only
+    /// cover the cycles which finish from now on.
+    ///
+    /// This doesn't affect when the next collection is triggered.
+    pub fn reset_cycle_stats(&mut self) {
+        self.context.reset_generation_stats();
+    }
+
+    /// Returns the number of objects, and the number of bytes they occupy, for each type
+    /// currently allocated in the arena, keyed by [`core::any::type_name`].
+    pub fn stats_by_type(&self) -> HashMap<&'static str, TypeStats> {
+        self.context.stats_by_type()
+    }
+
+    /// Runs a full collection, then panics if the number of objects still allocated isn't
+    /// `expected_live`, listing the types of every object still allocated.
+    ///
+    /// With the `debug-alloc-tags` feature, the objects are also listed by the call which
+    /// allocated them, such as `1 x u32 allocated at src/main.rs:10:24`.
+    ///
+    /// This is only available with debug assertions enabled.
+    #[cfg(debug_assertions)]
+    #[track_caller]
+    pub fn assert_collected(&mut self, expected_live: usize) {
+        self.complete_collection();
+
+        let live = self.allocations();
+        if live != expected_live {
+            #[cfg(not(feature = "debug-alloc-tags"))]
+            let types: Vec<_> = {
+                let mut types: Vec<_> = self.stats_by_type().into_iter().collect();
+                types.sort_by_key(|(name, _)| *name);
+
+                types
+                    .iter()
+                    .map(|(name, stats)| format!("{} x {name}", stats.count))
+                    .collect()
+            };
+
+            #[cfg(feature = "debug-alloc-tags")]
+            let types: Vec<_> = {
+                let mut sites: Vec<_> = self.context.allocation_sites().into_iter().collect();
+                sites.sort();
+
+                sites
+                    .iter()
+                    .map(|((name, site), count)| format!("{count} x {name} allocated at {site}"))
+                    .collect()
+            };
+
+            panic!(
+                "expected {expected_live} live objects after collection, found {live}: [{}]",
+                types.join(", ")
+            );
+        }
+    }
+
+    /// Marks every object reachable from the root, and returns the address and type name of each
+    /// object which wasn't reached, and so would be freed by the next sweep.
+    ///
+    /// The addresses are those returned by [`Gc::as_ptr`], so this can be used to find out which
+    /// objects are being kept alive, by checking which of them aren't listed. Objects which are
+    /// already waiting for their finalizers to be run, or which are only reachable through a
+    /// [`Weak`], are considered reachable. An unreachable object with a finalizer is listed like
+    /// any other, though the next collection queues its finalizer rather than freeing it.
+    ///
+    /// This restarts the mark phase of any collection which is in progress.
+    ///
+    /// [`Gc::as_ptr`]: crate::Gc::as_ptr
+    /// [`Weak`]: crate::Weak
+    pub fn unreachable_objects(&self) -> Vec<(*const (), &'static str)> {
+        self.context.unreachable_objects(&self.root)
+    }
+
+    /// Marks every object reachable from the root, and returns the address of each object
+    /// reached, in the order they were marked.
+    ///
+    /// The addresses are those returned by [`Gc::as_ptr`], so this can be used to check that
+    /// a [`Collect`] implementation traces every garbage collected pointer it owns.
+    ///
+    /// This restarts the mark phase of any collection which is in progress, and is only
+    /// available with debug assertions enabled.
+    ///
+    /// [`Gc::as_ptr`]: crate::Gc::as_ptr
+    #[cfg(debug_assertions)]
+    pub fn trace_report(&self) -> Vec<*const ()> {
+        self.context.trace_report(&self.root)
+    }
+}
+
+impl<R, A> Drop for Arena<R, A>
+where
+    A: Allocator,
+    R: Rootable,
+{
+    fn drop(&mut self) {
+        // Finalizers may dereference other objects, so they all have to run before any value is
+        // dropped.
+        self.context.finalize_all();
+    }
+}
+
+/// Copies every object reachable from the root into a new arena, with the same pacing, preserving
+/// shared and cyclic references.
+///
+/// Objects which are only reachable through a [`Rooted`] handle are not copied, and copied
+/// objects don't have finalizers.
+impl<R, A> Clone for Arena<R, A>
+where
+    R: Rootable,
+    for<'b> R::Root<'b>: DeepClone,
+    A: Allocator + Clone + 'static,
+{
+    fn clone(&self) -> Self {
+        let context = Box::new(Context::with_capacity_in(
+            self.context.pacing(),
+            self.context.allocator().clone(),
+            self.allocations(),
+        ));
+        let root = self.root.deep_clone(&DeepCloner::new(&*context));
+
+        Arena { context, root }
+    }
+}
+
+/// The state of an arena at some point, which it can be returned to with [`Arena::rollback`],
+/// as returned by [`Arena::checkpoint`].
+pub struct Checkpoint<R: Rootable> {
+    root: R::Root<'static>,
+    allocations: usize,
+    swept: usize,
+    arena: Weak<()>,
+}
+
+impl<R: Rootable> core::fmt::Debug for Checkpoint<R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Checkpoint")
+            .field("allocations", &self.allocations)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The position of an arena's collector within a cycle, as returned by
+/// [`Arena::save_collection_cursor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollectionCursor {
+    /// The number of cycles which had finished when the cursor was saved, which identifies the
+    /// cycle it was saved in.
+    pub cycle: usize,
+    pub phase: CursorPhase,
+}
+
+/// The phase of a collection cycle which a [`CollectionCursor`] was saved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorPhase {
+    /// No cycle is in progress.
+    Sleep,
+    /// Reachable objects are being marked, and `gray` of them are yet to be traced.
+    Mark { gray: usize },
+    /// Unreachable objects are being freed, and those before `index` have already been swept.
+    Sweep { index: usize },
+}
+
+/// Allocation statistics for a single type, as returned by [`Arena::stats_by_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TypeStats {
+    /// The number of allocated objects of the type.
+    pub count: usize,
+    /// The total number of bytes allocated for objects of the type, including the header of
+    /// each allocation.
+    pub bytes: usize,
+    /// The size of a single value of the type, not including the header, or `0` if it is
+    /// dynamically sized.
+    pub size: usize,
+    /// The alignment of the type, or `1` if it is dynamically sized.
+    pub align: usize,
+}
+
+/// How much a single collection freed, as returned by [`Arena::complete_collection_reporting`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CollectionReport {
+    /// The number of objects which were freed.
+    pub objects_freed: usize,
+    /// The number of bytes which were freed, including the header of each allocation.
+    pub bytes_freed: usize,
+    /// The number of objects still allocated after the collection.
+    pub objects_surviving: usize,
+}
+
+/// Statistics accumulated over every collection cycle of an arena, as returned by
+/// [`Arena::collect_generation_stats`], to help with choosing a [`Pacing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct GenerationStats {
+    /// The number of collection cycles which have finished.
+    pub completed_cycles: usize,
+    /// The total number of objects which survived the sweep of each cycle.
+    pub survived: usize,
+    /// The total number of objects which were freed by the sweep of each cycle.
+    pub freed: usize,
+    /// The number of objects which have been taken from the gray list to be traced.
+    pub gray_samples: usize,
+    /// The total length of the gray list each time an object was taken from it.
+    pub gray_len_total: usize,
+}
+
+impl GenerationStats {
+    /// Returns the average number of objects which survived each cycle.
+    pub fn survived_per_cycle(&self) -> f64 {
+        self.survived as f64 / self.completed_cycles.max(1) as f64
+    }
+
+    /// Returns the average length of the gray list while marking.
+    pub fn average_gray_len(&self) -> f64 {
+        self.gray_len_total as f64 / self.gray_samples.max(1) as f64
+    }
+
+    /// Returns the fraction of swept objects which were freed, or `0.0` if nothing has been
+    /// swept.
+    pub fn reclaim_ratio(&self) -> f64 {
+        let swept = self.freed + self.survived;
+        if swept == 0 {
+            0.0
+        } else {
+            self.freed as f64 / swept as f64
+        }
+    }
 }
 
 pub trait Rootable {
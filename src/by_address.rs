@@ -0,0 +1,226 @@
+use core::{cmp::Ordering, hash::Hash, ops::Deref};
+use std::{collections::HashMap, fmt::Debug};
+
+use crate::{Collect, Collector, Gc};
+
+/// A wrapper around a [`Gc`] which compares and hashes by the address of its allocation, rather
+/// than by the value it points to.
+///
+/// This gives identity semantics to pointers stored in sets and maps, and avoids hashing or
+/// comparing large pointees.
+///
+/// The ordering is only consistent for as long as the pointers are alive, and addresses differ
+/// between runs, so the iteration order of a [`BTreeMap`] of `ByAddress` keys shouldn't be relied
+/// upon, for example when serializing.
+///
+/// [`BTreeMap`]: std::collections::BTreeMap
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, ByAddress, Gc};
+/// # use std::collections::HashSet;
+/// # once_arena(|mt| {
+/// let a = Gc::new(5, mt);
+/// let b = Gc::new(5, mt);
+/// assert_eq!(a, b);
+///
+/// let set = HashSet::from([ByAddress(a), ByAddress(b), ByAddress(a)]);
+/// assert_eq!(set.len(), 2);
+/// # });
+/// ```
+#[repr(transparent)]
+pub struct ByAddress<'b, T: ?Sized>(pub Gc<'b, T>);
+
+impl<'b, T: ?Sized> ByAddress<'b, T> {
+    fn addr(&self) -> *const () {
+        self.0.as_ptr().cast::<()>()
+    }
+}
+
+impl<'b, T: ?Sized> Deref for ByAddress<'b, T> {
+    type Target = Gc<'b, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+unsafe impl<T: ?Sized> Collect for ByAddress<'_, T> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.0.trace(c);
+    }
+}
+
+impl<T: ?Sized> Clone for ByAddress<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for ByAddress<'_, T> {}
+
+impl<T: ?Sized + Debug> Debug for ByAddress<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ByAddress").field(&self.addr()).finish()
+    }
+}
+
+impl<T: ?Sized> PartialEq for ByAddress<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.addr(), other.addr())
+    }
+}
+
+impl<T: ?Sized> Eq for ByAddress<'_, T> {}
+
+impl<T: ?Sized> PartialOrd for ByAddress<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: ?Sized> Ord for ByAddress<'_, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.order_key().cmp(&other.0.order_key())
+    }
+}
+
+impl<T: ?Sized> Hash for ByAddress<'_, T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.addr().hash(state);
+    }
+}
+
+/// A map keyed by the identity of garbage collected objects, rather than by their values.
+///
+/// This is a [`HashMap`] of [`ByAddress`] keys, which takes and returns plain [`Gc`] pointers.
+/// Every key and value is traced, so the map keeps its keys alive; it has no interior
+/// mutability, so modifying one which is already behind a [`Gc`] requires a lock, such as a
+/// [`LockedRefCell`](crate::locked::LockedRefCell).
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, Gc, GcIdentityMap};
+/// # once_arena(|mt| {
+/// let a = Gc::new(5, mt);
+/// let b = Gc::new(5, mt);
+///
+/// let mut names = GcIdentityMap::new();
+/// names.insert(a, "a");
+/// names.insert(b, "b");
+/// assert_eq!(names.get(a), Some(&"a"));
+/// assert_eq!(names.get(b), Some(&"b"));
+/// # });
+/// ```
+pub struct GcIdentityMap<'b, K: ?Sized, V>(HashMap<ByAddress<'b, K>, V>);
+
+impl<'b, K: ?Sized, V> GcIdentityMap<'b, K, V> {
+    pub fn new() -> GcIdentityMap<'b, K, V> {
+        GcIdentityMap(HashMap::new())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Inserts a value for the given object, returning the value previously stored for it.
+    pub fn insert(&mut self, key: Gc<'b, K>, value: V) -> Option<V> {
+        self.0.insert(ByAddress(key), value)
+    }
+
+    pub fn get(&self, key: Gc<'b, K>) -> Option<&V> {
+        self.0.get(&ByAddress(key))
+    }
+
+    pub fn get_mut(&mut self, key: Gc<'b, K>) -> Option<&mut V> {
+        self.0.get_mut(&ByAddress(key))
+    }
+
+    pub fn contains_key(&self, key: Gc<'b, K>) -> bool {
+        self.0.contains_key(&ByAddress(key))
+    }
+
+    pub fn remove(&mut self, key: Gc<'b, K>) -> Option<V> {
+        self.0.remove(&ByAddress(key))
+    }
+
+    /// Returns an iterator over every entry, in an arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (Gc<'b, K>, &V)> {
+        self.0.iter().map(|(k, v)| (k.0, v))
+    }
+}
+
+impl<K: ?Sized, V> Default for GcIdentityMap<'_, K, V> {
+    fn default() -> Self {
+        GcIdentityMap::new()
+    }
+}
+
+impl<K: ?Sized + Debug, V: Debug> Debug for GcIdentityMap<'_, K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+unsafe impl<K: ?Sized, V: Collect> Collect for GcIdentityMap<'_, K, V> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        for (k, v) in self.0.iter() {
+            k.trace(c);
+            v.trace(c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{once_arena, ByAddress, Gc, GcIdentityMap};
+
+    #[test]
+    fn btree_map_by_address() {
+        once_arena(|mt| {
+            let gcs: Vec<_> = (0..10).map(|_| Gc::new(0u32, mt)).collect();
+
+            let mut map = BTreeMap::new();
+            for (i, gc) in gcs.iter().enumerate() {
+                map.insert(ByAddress(*gc), i);
+            }
+            map.insert(ByAddress(gcs[3]), 30);
+            assert_eq!(map.len(), 10);
+            assert_eq!(map[&ByAddress(gcs[3])], 30);
+
+            let addrs: Vec<_> = map.keys().map(|k| k.as_ptr().addr()).collect();
+            assert!(addrs.is_sorted());
+        });
+    }
+
+    #[test]
+    fn identity_map_distinct_equal_keys() {
+        once_arena(|mt| {
+            let a = Gc::new(String::from("key"), mt);
+            let b = Gc::new(String::from("key"), mt);
+            assert_eq!(a, b);
+
+            let mut map = GcIdentityMap::new();
+            assert_eq!(map.insert(a, 1), None);
+            assert_eq!(map.insert(b, 2), None);
+            assert_eq!(map.len(), 2);
+            assert_eq!(map.get(a), Some(&1));
+            assert_eq!(map.get(b), Some(&2));
+
+            assert_eq!(map.insert(a, 10), Some(1));
+            assert_eq!(map.remove(b), Some(2));
+            assert!(!map.contains_key(b));
+            assert_eq!(map.get(Gc::new(String::from("key"), mt)), None);
+        });
+    }
+}
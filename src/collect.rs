@@ -3,7 +3,7 @@ use std::{
     ops::Deref,
 };
 
-use crate::context::Collector;
+use crate::{context::Collector, Invariant};
 
 /// Used to mark each garbage collected pointer that can be reached from the implementing value.
 ///
@@ -19,13 +19,197 @@ use crate::context::Collector;
 /// garbage collected pointer to be adopted, modified, or replaces, except if that interior
 /// mutability is gated behind an [`Unlock`] implementation
 ///
+/// # Closures
+/// Boxed `'static` closures, such as `Box<dyn Fn(u32) -> u32>`, can be stored in garbage
+/// collected objects, as they cannot capture a garbage collected pointer.
+///
+/// ```
+/// # use ghost_gc::{once_arena, Gc};
+/// # once_arena(|mt| {
+/// let callback: Box<dyn Fn(u32) -> u32> = Box::new(|x| x + 1);
+/// let callback = Gc::new(callback, mt);
+/// assert_eq!(callback(1), 2);
+/// # });
+/// ```
+///
+/// ```compile_fail
+/// # use ghost_gc::{once_arena, Gc};
+/// # once_arena(|mt| {
+/// let value = Gc::new(5, mt);
+/// let callback: Box<dyn Fn() -> i32> = Box::new(move || *value);
+/// let callback = Gc::new(callback, mt);
+/// # });
+/// ```
+///
+/// # Recursive types
+/// The `NEEDS_TRACE` of a recursive type can't refer to itself, such as through a `Box<Self>`
+/// field, as evaluating it would be a cycle. A value can only reach another garbage collected
+/// pointer through its recursive fields if one of its other fields could hold it, so the
+/// recursive fields can be left out.
+///
+/// ```
+/// # use ghost_gc::{Collect, Collector, Gc};
+/// enum Tree<'b> {
+///     Leaf(Gc<'b, u32>),
+///     Node(Box<Tree<'b>>, Box<Tree<'b>>),
+/// }
+///
+/// unsafe impl Collect for Tree<'_> {
+///     const NEEDS_TRACE: bool = <Gc<u32> as Collect>::NEEDS_TRACE;
+///
+///     fn trace(&self, c: &Collector) {
+///         match self {
+///             Tree::Leaf(leaf) => leaf.trace(c),
+///             Tree::Node(left, right) => {
+///                 left.trace(c);
+///                 right.trace(c);
+///             }
+///         }
+///     }
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use ghost_gc::{Collect, Collector, Gc};
+/// # enum Tree<'b> {
+/// #     Leaf(Gc<'b, u32>),
+/// #     Node(Box<Tree<'b>>, Box<Tree<'b>>),
+/// # }
+/// unsafe impl Collect for Tree<'_> {
+///     const NEEDS_TRACE: bool =
+///         <Gc<u32> as Collect>::NEEDS_TRACE || <Box<Tree> as Collect>::NEEDS_TRACE;
+///     // ...
+/// #     fn trace(&self, c: &Collector) {}
+/// }
+/// # fn main() { assert!(<Tree as Collect>::NEEDS_TRACE); }
+/// ```
+///
+/// # Reference counting
+/// Reference counted pointers to `'static` values, such as `Rc<str>` or `Arc<[u8]>`, are leaves,
+/// as they cannot contain a garbage collected pointer. They can be used to share immutable data
+/// between arenas, or with the rest of a program. The same holds for any other `'static`
+/// reference counted pointer, such as `triomphe::Arc` with the `triomphe` feature.
+///
+/// ```
+/// # use ghost_gc::{once_arena, Gc};
+/// # use std::rc::Rc;
+/// # once_arena(|mt| {
+/// let name: Rc<str> = Rc::from("shared");
+/// let node = Gc::new((Rc::clone(&name), Gc::new(5, mt)), mt);
+/// assert_eq!(&*node.0, "shared");
+/// # });
+/// ```
+///
+/// ```compile_fail
+/// # use ghost_gc::{once_arena, Gc};
+/// # use std::rc::Rc;
+/// # once_arena(|mt| {
+/// let node = Gc::new(Rc::new(Gc::new(5, mt)), mt);
+/// # });
+/// ```
+///
+/// # Atomics
+/// The integer and boolean atomics are leaves, but `AtomicPtr` is deliberately not `Collect`, as
+/// it could be used to store a pointer into the arena which the collector can't see, or replace
+/// one without firing the write barrier. Interior mutability of garbage collected pointers must
+/// go through [`Gc::write`] and the [`locked`] types instead.
+///
+/// ```
+/// # use ghost_gc::{once_arena, Gc};
+/// # use core::sync::atomic::{AtomicUsize, Ordering};
+/// # once_arena(|mt| {
+/// let counter = Gc::new(AtomicUsize::new(0), mt);
+/// counter.fetch_add(1, Ordering::Relaxed);
+/// # });
+/// ```
+///
+/// ```compile_fail
+/// # use ghost_gc::{once_arena, Gc};
+/// # use core::sync::atomic::AtomicPtr;
+/// # once_arena(|mt| {
+/// let ptr = Gc::new(AtomicPtr::new(core::ptr::null_mut::<u32>()), mt);
+/// # });
+/// ```
+///
 /// [`Unlock`]: crate::locked::Unlock
+/// [`Gc::write`]: crate::Gc::write
+/// [`locked`]: crate::locked
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be stored in a garbage collected object, as it isn't `Collect`",
+    label = "doesn't implement `Collect`"
+)]
 pub unsafe trait Collect {
     const NEEDS_TRACE: bool;
 
     fn trace(&self, c: &Collector);
 }
 
+/// Checks that a struct and each of its fields implement [`Collect`], so that a hand written
+/// implementation can't forget a field which can't be traced.
+///
+/// Every field has to be listed by name, and the check fails to compile if any is missing. A
+/// field which doesn't implement `Collect` is reported by its type, at the invocation, rather
+/// than at a distant allocation.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{gc_check_collect, Collect, Collector, Gc};
+/// struct Node<'b> {
+///     value: u32,
+///     next: Option<Gc<'b, Node<'b>>>,
+/// }
+///
+/// unsafe impl Collect for Node<'_> {
+///     const NEEDS_TRACE: bool = true;
+///
+///     fn trace(&self, c: &Collector) {
+///         self.value.trace(c);
+///         self.next.trace(c);
+///     }
+/// }
+///
+/// gc_check_collect!(Node { value, next });
+/// ```
+///
+/// A field of a type which isn't `Collect`, such as a `Cell` of a garbage collected pointer, is
+/// rejected.
+///
+/// ```compile_fail
+/// # use ghost_gc::{gc_check_collect, Collect, Collector, Gc};
+/// # use std::cell::Cell;
+/// struct Node<'b> {
+///     value: u32,
+///     next: Cell<Option<Gc<'b, Node<'b>>>>,
+/// }
+///
+/// unsafe impl Collect for Node<'_> {
+/// #     const NEEDS_TRACE: bool = true;
+/// #     fn trace(&self, c: &Collector) {}
+///     // ...
+/// }
+///
+/// gc_check_collect!(Node { value, next });
+/// ```
+#[macro_export]
+macro_rules! gc_check_collect {
+    ($name:path { $($field:ident),* $(,)? }) => {
+        const _: () = {
+            fn check_collect<T: $crate::Collect + ?Sized>(_: &T) {}
+
+            #[allow(unused)]
+            fn check_fields(value: &$name) {
+                // Fails if any field isn't listed.
+                let $name { $($field),* } = value;
+
+                check_collect(value);
+                $(
+                    check_collect($field);
+                )*
+            }
+        };
+    };
+}
+
 macro_rules! unsafe_impl_collect {
     ($t:ty) => {
         unsafe impl Collect for $t {
@@ -58,31 +242,147 @@ unsafe_impl_collect!(
     (),
     f32,
     f64,
-    str
+    str,
+    alloc::string::String,
+    alloc::ffi::CString,
+    core::ffi::CStr,
+    std::ffi::OsString,
+    std::ffi::OsStr,
+    std::path::PathBuf,
+    std::path::Path,
+    core::sync::atomic::AtomicBool,
+    core::sync::atomic::AtomicU8,
+    core::sync::atomic::AtomicU16,
+    core::sync::atomic::AtomicU32,
+    core::sync::atomic::AtomicU64,
+    core::sync::atomic::AtomicUsize,
+    core::sync::atomic::AtomicI8,
+    core::sync::atomic::AtomicI16,
+    core::sync::atomic::AtomicI32,
+    core::sync::atomic::AtomicI64,
+    core::sync::atomic::AtomicIsize,
+    core::net::IpAddr,
+    core::net::Ipv4Addr,
+    core::net::Ipv6Addr,
+    core::net::SocketAddr,
+    core::net::SocketAddrV4,
+    core::net::SocketAddrV6
 );
 
-unsafe impl<T> Collect for &T
-where
-    T: Collect,
-{
-    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+// Arithmetic wrappers only ever contain integers.
+unsafe impl<T: 'static> Collect for core::num::Wrapping<T> {
+    const NEEDS_TRACE: bool = false;
 
-    fn trace(&self, c: &Collector) {
-        (*self).trace(c)
-    }
+    fn trace(&self, _: &Collector) {}
 }
 
-unsafe impl<T> Collect for &mut T
+unsafe impl<T: 'static> Collect for core::num::Saturating<T> {
+    const NEEDS_TRACE: bool = false;
+
+    fn trace(&self, _: &Collector) {}
+}
+
+// SIMD vectors are over-aligned, which the layout of every allocation respects.
+#[cfg(target_arch = "x86_64")]
+unsafe_impl_collect!(
+    core::arch::x86_64::__m128,
+    core::arch::x86_64::__m128d,
+    core::arch::x86_64::__m128i,
+    core::arch::x86_64::__m256,
+    core::arch::x86_64::__m256d,
+    core::arch::x86_64::__m256i
+);
+
+/// Implements `Collect` for a generic type which owns or refers to a `'static` value, and
+/// therefore cannot contain a garbage collected pointer.
+macro_rules! unsafe_impl_collect_static {
+    ($t:ty) => {
+        unsafe impl<T: ?Sized + 'static> Collect for $t {
+            const NEEDS_TRACE: bool = false;
+
+            fn trace(&self, _: &Collector) {}
+        }
+    };
+    ($($t:ty),*) => {
+        $(
+            unsafe_impl_collect_static!($t);
+        )*
+    };
+}
+
+/// A marker for smart pointers which own or borrow their target, such that tracing the pointer
+/// only needs to trace its target.
+///
+/// Any type implementing this trait implements [`Collect`] by forwarding to [`Deref::deref`].
+///
+/// # Safety
+/// The target returned by `deref` must be the only value reachable from the pointer which could
+/// contain a garbage collected pointer, and it must be kept alive for as long as the pointer is.
+/// Any interior mutability of the target has to go through an [`Unlock`] implementation, as with
+/// [`Collect`].
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, Gc, TransparentCollect};
+/// # use core::ops::Deref;
+/// struct MyBox<T>(Box<T>);
+///
+/// impl<T> Deref for MyBox<T> {
+///     type Target = T;
+///
+///     fn deref(&self) -> &T {
+///         &self.0
+///     }
+/// }
+///
+/// unsafe impl<T> TransparentCollect for MyBox<T> {}
+///
+/// # once_arena(|mt| {
+/// let inner = Gc::new(5, mt);
+/// let outer = Gc::new(MyBox(Box::new(inner)), mt);
+/// assert_eq!(***outer, 5);
+/// # });
+/// ```
+///
+/// [`Unlock`]: crate::locked::Unlock
+pub unsafe trait TransparentCollect: Deref {}
+
+unsafe impl<P> Collect for P
 where
-    T: Collect,
+    P: TransparentCollect,
+    P::Target: Collect,
 {
-    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+    const NEEDS_TRACE: bool = <P::Target as Collect>::NEEDS_TRACE;
 
     fn trace(&self, c: &Collector) {
-        (**self).trace(c);
+        self.deref().trace(c);
     }
 }
 
+unsafe impl<T: ?Sized> TransparentCollect for &T {}
+unsafe impl<T: ?Sized> TransparentCollect for &mut T {}
+unsafe impl<T: ?Sized> TransparentCollect for alloc::boxed::Box<T> {}
+unsafe impl<T> TransparentCollect for ManuallyDrop<T> {}
+// The owned forms of these dereference to the same values as the borrowed forms, so every
+// pointer the `Cow` owns is traced. A `Cow` of an arbitrary `ToOwned` type could hide some.
+unsafe impl TransparentCollect for alloc::borrow::Cow<'_, str> {}
+unsafe impl<T: Clone> TransparentCollect for alloc::borrow::Cow<'_, [T]> {}
+
+unsafe_impl_collect_static!(
+    alloc::rc::Rc<T>,
+    alloc::rc::Weak<T>,
+    alloc::sync::Arc<T>,
+    alloc::sync::Weak<T>
+);
+
+#[cfg(feature = "triomphe")]
+unsafe_impl_collect_static!(triomphe::Arc<T>);
+
+// Raw pointers are assumed to point to data which isn't managed by any arena. The `'static`
+// bound prevents pointers to branded types, but a raw pointer to a garbage collected value
+// is never traced, and must not be dereferenced after the value is collected.
+unsafe_impl_collect_static!(*const T, *mut T, core::ptr::NonNull<T>);
+
 macro_rules! unsafe_impl_collect_iterable {
     ($t:ty) => {
         unsafe impl<T> Collect for $t
@@ -92,9 +392,12 @@ macro_rules! unsafe_impl_collect_iterable {
             const NEEDS_TRACE: bool = T::NEEDS_TRACE;
 
             fn trace(&self, c: &Collector) {
-                #[allow(for_loops_over_fallibles)]
-                for el in self {
-                    el.trace(c);
+                // Collections of leaf values are never iterated.
+                if Self::NEEDS_TRACE {
+                    #[allow(for_loops_over_fallibles)]
+                    for el in self {
+                        el.trace(c);
+                    }
                 }
             }
         }
@@ -120,12 +423,89 @@ unsafe impl<T: Collect, const N: usize> Collect for [T; N] {
     const NEEDS_TRACE: bool = T::NEEDS_TRACE;
 
     fn trace(&self, c: &Collector) {
-        for el in self {
-            el.trace(c);
+        if T::NEEDS_TRACE {
+            for el in self {
+                el.trace(c);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+unsafe impl<T: Collect, const CAP: usize> Collect for arrayvec::ArrayVec<T, CAP> {
+    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+
+    fn trace(&self, c: &Collector) {
+        self.as_slice().trace(c);
+    }
+}
+
+// The unused capacity of a `tinyvec` array is initialized, and can be read through safe methods
+// such as `into_inner` and `grab_spare_slice`, so the whole backing array is traced.
+#[cfg(feature = "tinyvec")]
+unsafe impl<A: tinyvec::Array> Collect for tinyvec::ArrayVec<A>
+where
+    A::Item: Collect,
+{
+    const NEEDS_TRACE: bool = A::Item::NEEDS_TRACE;
+
+    fn trace(&self, c: &Collector) {
+        self.as_slice().trace(c);
+        self.grab_spare_slice().trace(c);
+    }
+}
+
+#[cfg(feature = "tinyvec")]
+unsafe impl<A: tinyvec::Array> Collect for tinyvec::TinyVec<A>
+where
+    A::Item: Collect,
+{
+    const NEEDS_TRACE: bool = A::Item::NEEDS_TRACE;
+
+    fn trace(&self, c: &Collector) {
+        match self {
+            tinyvec::TinyVec::Inline(inline) => inline.trace(c),
+            tinyvec::TinyVec::Heap(heap) => heap.trace(c),
         }
     }
 }
 
+unsafe impl<T: Collect, S> Collect for std::collections::HashSet<T, S> {
+    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+
+    fn trace(&self, c: &Collector) {
+        if T::NEEDS_TRACE {
+            for el in self {
+                el.trace(c);
+            }
+        }
+    }
+}
+
+macro_rules! unsafe_impl_collect_map {
+    ($t:ty $(, $s:ident)?) => {
+        unsafe impl<K, V $(, $s)?> Collect for $t
+        where
+            K: Collect,
+            V: Collect,
+        {
+            const NEEDS_TRACE: bool = K::NEEDS_TRACE || V::NEEDS_TRACE;
+
+            fn trace(&self, c: &Collector) {
+                if Self::NEEDS_TRACE {
+                    for (k, v) in self {
+                        k.trace(c);
+                        v.trace(c);
+                    }
+                }
+            }
+        }
+    };
+}
+
+unsafe_impl_collect_map!(std::collections::HashMap<K, V, S>, S);
+unsafe_impl_collect_map!(alloc::collections::BTreeMap<K, V>);
+
 /// This implementation is sound because the value contained within the `MaybeUninit` cannot be
 /// accessed without unsafe code. What this does mean is that an additional safety condition is
 /// added to [`MaybeUninit::assume_init`], which is that it can only be called within the same
@@ -136,15 +516,66 @@ unsafe impl<T> Collect for MaybeUninit<T> {
     fn trace(&self, _: &Collector) {}
 }
 
-unsafe impl<T> Collect for ManuallyDrop<T>
-where
-    T: Collect,
-{
-    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+unsafe impl<T: ?Sized> Collect for core::marker::PhantomData<T> {
+    const NEEDS_TRACE: bool = false;
 
-    fn trace(&self, c: &Collector) {
-        self.deref().trace(c);
-    }
+    fn trace(&self, _: &Collector) {}
+}
+
+/// An [`Invariant`] can be embedded in a collected type, to brand it with the arena's lifetime.
+///
+/// ```
+/// # use ghost_gc::{once_arena, Collect, Collector, Gc, Invariant};
+/// struct Branded<'b> {
+///     value: u32,
+///     brand: Invariant<'b>,
+/// }
+///
+/// unsafe impl Collect for Branded<'_> {
+///     const NEEDS_TRACE: bool = false;
+///
+///     fn trace(&self, c: &Collector) {
+///         self.value.trace(c);
+///         self.brand.trace(c);
+///     }
+/// }
+///
+/// # once_arena(|mt| {
+/// let branded = Gc::new(Branded { value: 5, brand: Invariant }, mt);
+/// assert_eq!(branded.value, 5);
+/// # });
+/// ```
+unsafe impl Collect for Invariant<'_> {
+    const NEEDS_TRACE: bool = false;
+
+    fn trace(&self, _: &Collector) {}
+}
+
+/// An `UnsafeCell` is a leaf, as long as it can only contain `'static` values, as a garbage
+/// collected pointer could otherwise be replaced without the knowledge of the collector. Interior
+/// mutability of garbage collected pointers must go through [`Gc::write`] instead.
+///
+/// ```
+/// # use ghost_gc::{once_arena, Gc};
+/// # use core::cell::UnsafeCell;
+/// # once_arena(|mt| {
+/// let cell = Gc::new(UnsafeCell::new(5), mt);
+/// # });
+/// ```
+///
+/// ```compile_fail
+/// # use ghost_gc::{once_arena, Gc};
+/// # use core::cell::UnsafeCell;
+/// # once_arena(|mt| {
+/// let cell = Gc::new(UnsafeCell::new(Gc::new(5, mt)), mt);
+/// # });
+/// ```
+///
+/// [`Gc::write`]: crate::Gc::write
+unsafe impl<T: ?Sized + 'static> Collect for core::cell::UnsafeCell<T> {
+    const NEEDS_TRACE: bool = false;
+
+    fn trace(&self, _: &Collector) {}
 }
 
 unsafe impl<T, E> Collect for Result<T, E>
@@ -175,6 +606,25 @@ where
     }
 }
 
+macro_rules! fn_impl {
+    ($($t:ident),*) => {
+        /// A `'static` closure is a leaf, as it cannot capture a garbage collected pointer.
+        unsafe impl<Ret, $( $t ),*> Collect for dyn Fn($( $t ),*) -> Ret + 'static {
+            const NEEDS_TRACE: bool = false;
+
+            fn trace(&self, _: &Collector) {}
+        }
+    };
+}
+
+fn_impl!();
+fn_impl!(A);
+fn_impl!(A, B);
+fn_impl!(A, B, C);
+fn_impl!(A, B, C, D);
+fn_impl!(A, B, C, D, E);
+fn_impl!(A, B, C, D, E, F);
+
 macro_rules! tuple_impl {
     ($($t:ident),+) => {
         unsafe impl< $( $t: Collect ),+ > Collect for ($($t,)+)
@@ -1,15 +1,22 @@
 use core::{alloc::Layout, ptr::Pointee};
 use std::{
-    alloc::{Allocator, Global},
+    alloc::{AllocError, Allocator, Global},
     cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
     ptr::NonNull,
+    rc::Rc,
 };
 
 use crate::{
-    gc_box::{Colour, Erased, GcBox, GcInner},
-    Collect, Invariant,
+    arena::{CollectionCursor, CursorPhase, GenerationStats, TypeStats},
+    gc_box::{Colour, Erased, GcBox, GcInner, GrayList},
+    rooted::{PinGuard, RootSet},
+    Collect, Finalize, Gc, Invariant, PartialSlice, Rooted,
 };
 
+#[cfg(feature = "debug-alloc-tags")]
+use core::panic::Location;
+
 #[repr(transparent)]
 pub struct Mutation<'b>(Invariant<'b>, Context<dyn Allocator>);
 
@@ -28,12 +35,141 @@ impl<'b> Mutation<'b> {
         &self.1
     }
 
+    /// Returns `true` if the object was allocated in this arena.
+    ///
+    /// The `'b` brand already guarantees this for safe code, so this is only useful for catching
+    /// misuse of unsafe code, and is only available with debug assertions enabled.
+    #[cfg(debug_assertions)]
+    pub fn owns<T: ?Sized>(&self, gc: &Gc<'b, T>) -> bool {
+        self.context().contains(gc.into_box().into_raw())
+    }
+
+    /// Pins the object, keeping it alive until the returned guard is dropped, even if nothing in
+    /// the arena refers to it. The arena still frees the object if it is dropped first.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{Arena, Collect, Collector, Gc, Rootable};
+    /// # struct Empty;
+    /// # unsafe impl Collect for Empty {
+    /// #     const NEEDS_TRACE: bool = false;
+    /// #     fn trace(&self, _c: &Collector) {}
+    /// # }
+    /// # impl Rootable for Empty { type Root<'l> = Empty; }
+    /// let mut arena = Arena::<Empty>::new(|_| Empty);
+    ///
+    /// let pin = arena.view(|_, mt| mt.pin(Gc::new(5u32, mt)));
+    /// arena.complete_collection();
+    ///
+    /// // Safety: The object is kept alive by the pin.
+    /// assert_eq!(unsafe { *pin.as_ptr() }, 5);
+    /// ```
+    pub fn pin<T: ?Sized>(&self, gc: Gc<'b, T>) -> PinGuard<T> {
+        PinGuard::new(self.root(gc))
+    }
+
+    /// Roots the object, keeping it alive until the returned handle is dropped, even if nothing
+    /// in the arena refers to it.
+    ///
+    /// Unlike the object itself, the handle isn't branded, so it can be stored outside of the
+    /// arena, such as in a [`ThreadLocalRoots`](crate::ThreadLocalRoots).
+    pub fn root<T: ?Sized>(&self, gc: Gc<'b, T>) -> Rooted<T> {
+        // The root set has to be traced again, in case the object is only reachable from a part
+        // of the root which has already been traced.
+        self.context().set_root_untraced();
+
+        // Safety: The object was allocated in this arena, as it has the same brand.
+        unsafe { self.context().explicit_roots().register(gc.into_box()) }
+    }
+
+    /// Returns how much has been allocated since the last collection started, relative to the
+    /// triggers of the arena's [`Pacing`].
+    ///
+    /// This is `0.0` just after a collection starts, and reaches `1.0` once enough has been
+    /// allocated for the pacing to start the next one. Whichever of the allocation and byte
+    /// triggers is closer to being met decides the pressure, and an arena with
+    /// [manual](Pacing::manual) pacing has no pressure. Embedders can use it to decide how much
+    /// collection work to do each frame.
+    pub fn allocation_pressure(&self) -> f32 {
+        self.context().allocation_pressure()
+    }
+
+    /// Allocates every value of the iterator, returning a pointer to each in order.
+    ///
+    /// This is equivalent to calling [`Gc::new`] for each value, except that the arena's list of
+    /// objects is only borrowed, and grown, once for the whole batch. The cost of each
+    /// allocation is otherwise the same, and is usually dominated by the allocator.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::once_arena;
+    /// # once_arena(|mt| {
+    /// let squares = mt.alloc_batch((0..4u32).map(|i| i * i));
+    /// assert_eq!(squares.iter().map(|x| **x).collect::<Vec<_>>(), [0, 1, 4, 9]);
+    /// # });
+    /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn alloc_batch<T, I>(&self, iter: I) -> Vec<Gc<'b, T>>
+    where
+        T: Collect,
+        I: IntoIterator<Item = T>,
+    {
+        // The iterator may allocate, so it is drained before the objects are borrowed.
+        let values: Vec<T> = iter.into_iter().collect();
+
+        self.context()
+            .allocate_batch(values)
+            .into_iter()
+            .map(|gc| unsafe { Gc::from_box(gc) })
+            .collect()
+    }
+
+    /// Allocates every value of the iterator, then calls `wire` with a pointer to each, so that
+    /// they can be made to point to each other, including in cycles.
+    ///
+    /// The values are fully initialized before `wire` is called, so the edges between them must
+    /// start out empty, and be set through an interiorly mutable type such as a [`LockedCell`],
+    /// whose write barrier keeps them traced.
+    ///
+    /// [`LockedCell`]: crate::locked::LockedCell
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn alloc_graph<T, I, F>(&self, values: I, wire: F) -> Vec<Gc<'b, T>>
+    where
+        T: Collect,
+        I: IntoIterator<Item = T>,
+        F: FnOnce(&[Gc<'b, T>]),
+    {
+        let nodes = self.alloc_batch(values);
+        wire(&nodes);
+        nodes
+    }
+
+    /// Allocates an uninitialized slice with room for `capacity` elements, which can be filled in
+    /// over the course of several collections. See [`PartialSlice`] for more.
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn slice_builder<T>(&self, capacity: usize) -> PartialSlice<'b, T> {
+        PartialSlice::new(capacity, self)
+    }
+
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub(crate) fn allocate<T>(&self, meta: <T as Pointee>::Metadata, layout: Layout) -> GcBox<T>
     where
         T: Collect,
     {
         self.context().allocate(meta, layout)
     }
+
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub(crate) fn try_allocate<T>(
+        &self,
+        meta: <T as Pointee>::Metadata,
+        layout: Layout,
+    ) -> Result<GcBox<T>, AllocError>
+    where
+        T: ?Sized + Collect,
+    {
+        self.context().try_allocate(meta, layout)
+    }
 }
 
 impl core::fmt::Debug for Mutation<'_> {
@@ -67,46 +203,117 @@ impl core::fmt::Debug for Collector {
     }
 }
 
+type WeakClearedFn = Box<dyn FnMut(*const ())>;
+
 pub(crate) struct Context<A = Global>
 where
     A: Allocator + ?Sized,
 {
-    newly_allocated: RefCell<Vec<GcBox<Erased>>>,
     objects: RefCell<Vec<GcBox<Erased>>>,
     trace_root: Cell<bool>,
-    first_gray: Cell<Option<GcBox<Erased>>>,
+    explicit_roots: RootSet,
+    finalization_queue: RefCell<VecDeque<GcBox<Erased>>>,
+    /// Every object with a finalizer which hasn't been queued yet.
+    finalizable: RefCell<Vec<GcBox<Erased>>>,
+    on_weak_cleared: RefCell<Option<WeakClearedFn>>,
+    gray: GrayList,
+    /// The objects turned gray by a write barrier, which are traced again once the gray list is
+    /// empty. Every object points to this, so it is kept behind an `Rc`, which isn't moved along
+    /// with the context.
+    regrayed: Rc<GrayList>,
+    generation_stats: Cell<GenerationStats>,
     phase: Cell<CollectionPhase>,
     cycle_allocations: Cell<usize>,
     cycle_bytes: Cell<usize>,
+    live_bytes: Cell<usize>,
+    /// The number of objects freed by every sweep so far. Sweeping reorders `objects`, so this
+    /// changing means that a checkpoint's count of objects no longer marks where it was taken.
+    swept: Cell<usize>,
+    /// The number of cycles which have finished. Unlike the generation stats, this is never
+    /// reset, so that it identifies the cycle a [`CollectionCursor`] was saved in.
+    cycles: Cell<usize>,
+    /// The number of objects allocated while marking or sweeping since the last step, which is
+    /// added to the stride of the next one.
+    debt: Cell<usize>,
     pacing: Pacing,
+    #[cfg(debug_assertions)]
+    trace_log: RefCell<Option<Vec<*const ()>>>,
+    /// The address of every live allocation, which is checked when it is freed, as nothing may
+    /// depend on an object having moved.
+    #[cfg(debug_assertions)]
+    addresses: RefCell<std::collections::HashSet<usize>>,
     alloc: A,
 }
 
 impl<A: Allocator> Context<A> {
-    pub(crate) fn new_in(pacing: Pacing, alloc: A) -> Context<A>
+    pub(crate) fn with_capacity_in(pacing: Pacing, alloc: A, capacity: usize) -> Context<A>
     where
         A: Allocator + 'static,
     {
+        pacing.validate();
+
         Context {
-            newly_allocated: Default::default(),
-            objects: Default::default(),
+            objects: RefCell::new(Vec::with_capacity(capacity)),
             trace_root: Default::default(),
-            first_gray: Default::default(),
+            explicit_roots: RootSet::new(),
+            finalization_queue: Default::default(),
+            finalizable: Default::default(),
+            on_weak_cleared: Default::default(),
+            gray: GrayList::default(),
+            regrayed: Default::default(),
+            generation_stats: Default::default(),
             phase: Default::default(),
             cycle_allocations: Cell::new(0),
             cycle_bytes: Cell::new(0),
+            live_bytes: Cell::new(0),
+            swept: Cell::new(0),
+            cycles: Cell::new(0),
+            debt: Cell::new(0),
             pacing,
+            #[cfg(debug_assertions)]
+            trace_log: Default::default(),
+            #[cfg(debug_assertions)]
+            addresses: Default::default(),
             alloc,
         }
     }
 
-    fn trace_next(&self, root: &impl Collect) -> bool {
+    pub fn pacing(&self) -> Pacing {
+        self.pacing
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Makes a single step of progress in marking, returning the number of objects traced, or
+    /// `None` if marking has finished.
+    fn trace_next(&self, root: &impl Collect) -> Option<usize> {
+        match self.trace_next_reachable(root) {
+            Some(traced) => Some(traced),
+            None => self.queue_finalizers().then_some(0),
+        }
+    }
+
+    /// Makes a single step of progress in marking, as with [`Context::trace_next`], without
+    /// queueing unreachable objects with finalizers, so that only reachable objects are marked.
+    fn trace_next_reachable(&self, root: &impl Collect) -> Option<usize> {
         if self.trace_root.get() {
             root.trace(Collector::new(self));
+            self.explicit_roots.trace(Collector::new(self));
+            for obj in self.finalization_queue.borrow().iter() {
+                self.mark_box(*obj);
+            }
             self.set_root_traced();
 
-            true
+            Some(0)
         } else if let Some(val) = self.take_next_box() {
+            let gray_len = self.gray.len();
+            self.update_stats(|stats| {
+                stats.gray_samples += 1;
+                stats.gray_len_total += gray_len + 1;
+            });
+
             match val.colour() {
                 Colour::White | Colour::Weak => unreachable!(),
                 Colour::Gray => {
@@ -116,46 +323,166 @@ impl<A: Allocator> Context<A> {
                 Colour::Black => {}
             }
 
-            true
+            Some(1)
+        } else if self.push_regrayed() {
+            Some(0)
         } else {
-            false
+            None
+        }
+    }
+
+    /// Adds every unreachable object with a finalizer to the finalization queue, and marks it
+    /// so that it and everything it references survives until the finalizer runs. Returns
+    /// whether there were any.
+    fn queue_finalizers(&self) -> bool {
+        let mut found = false;
+
+        self.finalizable.borrow_mut().retain(|obj| {
+            if matches!(obj.colour(), Colour::White | Colour::Weak) {
+                self.finalization_queue.borrow_mut().push_back(*obj);
+                self.mark_box(*obj);
+                found = true;
+                false
+            } else {
+                true
+            }
+        });
+
+        found
+    }
+
+    /// Runs the finalizers of up to `max` objects in the finalization queue, in the order they
+    /// were queued, returning how many were run.
+    pub fn run_finalizers(&self, max: usize) -> usize {
+        let mut count = 0;
+
+        while count < max {
+            // Finalizers may allocate, so the queue can't be borrowed while they're running.
+            let Some(obj) = self.finalization_queue.borrow_mut().pop_front() else {
+                break;
+            };
+
+            unsafe { obj.vtable().finalize(obj, Mutation::new(self)) };
+            count += 1;
         }
+
+        count
+    }
+
+    /// Runs the finalizer of every object which hasn't been finalized yet, whether or not it is
+    /// reachable, including those of objects allocated by other finalizers.
+    pub fn finalize_all(&self) {
+        loop {
+            self.finalization_queue
+                .borrow_mut()
+                .extend(self.finalizable.borrow_mut().drain(..));
+
+            if self.run_finalizers(usize::MAX) == 0 {
+                break;
+            }
+        }
+    }
+
+    pub fn pending_finalizers(&self) -> usize {
+        self.finalization_queue.borrow().len()
+    }
+
+    /// Moves every object which was turned gray by a write barrier onto the gray list, returning
+    /// whether there were any.
+    fn push_regrayed(&self) -> bool {
+        let found = !self.regrayed.is_empty();
+
+        while let Some(obj) = self.regrayed.pop() {
+            self.gray.push(obj);
+        }
+
+        found
     }
 
     fn take_next_box(&self) -> Option<GcBox<Erased>> {
-        let ptr = self.first_gray.get()?;
-        let next = ptr.next_gc().take();
-        self.first_gray.set(next);
+        let ptr = self.gray.pop()?;
+        ptr.set_regray_list(self.regray_list());
         Some(ptr)
     }
 
-    pub fn set_root_untraced(&self) {
-        self.trace_root.set(true);
+    pub fn generation_stats(&self) -> GenerationStats {
+        self.generation_stats.get()
     }
 
-    pub fn set_root_traced(&self) {
-        self.trace_root.set(false);
+    pub fn reset_generation_stats(&self) {
+        self.generation_stats.set(GenerationStats::default());
+    }
+
+    fn update_stats(&self, f: impl FnOnce(&mut GenerationStats)) {
+        let mut stats = self.generation_stats.get();
+        f(&mut stats);
+        self.generation_stats.set(stats);
+    }
+
+    /// Returns `true` if a collection is in progress, or if enough has been allocated since the
+    /// last one for the pacing to start a new one.
+    pub fn needs_collection(&self) -> bool {
+        match self.phase.get() {
+            CollectionPhase::Sleep => self
+                .pacing
+                .should_wake(self.cycle_allocations.get(), self.cycle_bytes.get()),
+            CollectionPhase::Mark | CollectionPhase::Sweep { .. } => true,
+        }
     }
 
     pub fn allocations(&self) -> usize {
-        self.objects.borrow().len() + self.newly_allocated.borrow().len()
+        self.objects.borrow().len()
+    }
+
+    pub fn live_bytes(&self) -> usize {
+        self.live_bytes.get()
+    }
+
+    pub fn set_on_weak_cleared(&self, f: WeakClearedFn) {
+        *self.on_weak_cleared.borrow_mut() = Some(f);
+    }
+
+    /// Returns the number of objects allocated by each call, along with their type.
+    #[cfg(all(debug_assertions, feature = "debug-alloc-tags"))]
+    pub fn allocation_sites(&self) -> HashMap<(&'static str, &'static Location<'static>), usize> {
+        let mut sites = HashMap::new();
+
+        for obj in self.objects.borrow().iter() {
+            let key = (obj.vtable().type_name(), obj.site());
+            *sites.entry(key).or_default() += 1;
+        }
+
+        sites
+    }
+
+    pub fn stats_by_type(&self) -> HashMap<&'static str, TypeStats> {
+        let mut stats = HashMap::<&'static str, TypeStats>::new();
+
+        for obj in self.objects.borrow().iter() {
+            let vtable = obj.vtable();
+            let entry = stats.entry(vtable.type_name()).or_default();
+            entry.size = vtable.size();
+            entry.align = vtable.align();
+            entry.count += 1;
+            entry.bytes += obj.layout().size();
+        }
+
+        stats
     }
 
     pub fn advance_phase(&self) -> bool {
         match self.phase.get() {
             CollectionPhase::Sleep => {
-                self.objects
-                    .borrow_mut()
-                    .append(&mut *self.newly_allocated.borrow_mut());
-
                 self.cycle_allocations.set(0);
                 self.cycle_bytes.set(0);
 
                 self.set_root_untraced();
+                self.clear_gray();
+                self.debt.set(0);
 
                 for obj in self.objects.borrow().iter() {
                     unsafe { obj.set_colour(Colour::White) };
-                    obj.set_next(None);
+                    obj.set_regray_list(self.regray_list());
                 }
 
                 self.phase.set(CollectionPhase::Mark);
@@ -163,12 +490,27 @@ impl<A: Allocator> Context<A> {
                 false
             }
             CollectionPhase::Mark => {
+                // The values of weakly reachable objects are dropped as soon as marking ends,
+                // so that they can't be upgraded, and made reachable again, during the sweep.
+                // Their storage is kept until they are no longer reachable by any weak pointer.
+                let mut on_weak_cleared = self.on_weak_cleared.borrow_mut();
+                for obj in self.objects.borrow().iter() {
+                    if obj.colour() == Colour::Weak && obj.is_initialized() {
+                        unsafe { obj.drop_in_place() };
+                        if let Some(f) = &mut *on_weak_cleared {
+                            f(obj.data_ptr().cast_const().cast());
+                        }
+                    }
+                }
+
                 self.phase.set(CollectionPhase::Sweep { index: 0 });
 
                 false
             }
             CollectionPhase::Sweep { .. } => {
                 self.phase.set(CollectionPhase::Sleep);
+                self.update_stats(|stats| stats.completed_cycles += 1);
+                self.cycles.set(self.cycles.get() + 1);
 
                 true
             }
@@ -192,112 +534,423 @@ impl<A: Allocator> Context<A> {
                 }
             }
             CollectionPhase::Mark => {
+                // Every object allocated since the last step adds to the work of this one, so
+                // that marking always outpaces the mutator, and is guaranteed to finish.
+                let stride = pacing.mark_stride.saturating_add(self.debt.replace(0));
                 let mut marked = 0;
 
-                dbg!(&self.first_gray);
-                while self.trace_next(root) {
-                    marked += 1;
+                let guard = AbortCycleOnUnwind(self);
 
-                    dbg!(&self.first_gray);
+                while let Some(traced) = self.trace_next(root) {
+                    marked += traced;
 
-                    if marked >= self.pacing.mark_stride {
+                    if marked >= stride {
+                        core::mem::forget(guard);
                         return;
                     }
                 }
 
+                core::mem::forget(guard);
                 self.advance_phase();
             }
             CollectionPhase::Sweep { index } => {
                 let objects = &mut *self.objects.borrow_mut();
 
+                // Objects allocated during the sweep are added to the end of the list, and have to
+                // be passed over too, so they add to the work of the next step, as when marking.
+                let stride = pacing.sweep_stride.saturating_add(self.debt.replace(0));
+
                 let mut current = index;
-                let mut end =
-                    std::cmp::min(index.saturating_add(pacing.sweep_stride), objects.len());
+                let mut end = std::cmp::min(index.saturating_add(stride), objects.len());
+                let (mut freed, mut survived) = (0, 0);
 
                 while current < end {
-                    dbg!(&objects, current, end);
                     let obj = objects[current];
 
                     match obj.colour() {
                         Colour::White => {
+                            // Foreign code may still use a pointer to the object, which is about
+                            // to dangle.
+                            #[cfg(debug_assertions)]
+                            assert!(
+                                obj.raw_handles() == 0,
+                                "an object was collected while a pointer returned by \
+                                 `Gc::into_raw` had yet to be passed back to `Gc::from_raw`"
+                            );
+
                             unsafe { obj.drop_in_place() };
                             objects.swap_remove(current);
                             unsafe { self.deallocate(obj) };
                             end -= 1;
+                            freed += 1;
                             continue;
                         }
-                        Colour::Gray => unreachable!(),
-                        Colour::Weak => {
-                            unsafe { obj.drop_in_place() };
-                            obj.set_uninit();
-                            current += 1;
-                            continue;
-                        }
-                        Colour::Black => {
+                        // Objects can be turned gray by a write barrier after marking has
+                        // finished, but they were still reachable.
+                        Colour::Weak | Colour::Gray | Colour::Black => {
                             current += 1;
+                            survived += 1;
                             continue;
                         }
                     }
                 }
 
+                self.update_stats(|stats| {
+                    stats.freed += freed;
+                    stats.survived += survived;
+                });
+                self.swept.set(self.swept.get() + freed);
+
                 if end == objects.len() {
                     self.advance_phase();
+                } else {
+                    self.phase.set(CollectionPhase::Sweep { index: current });
                 }
             }
         }
     }
 
-    /// Runs the collection cycle until all allocated objects have been marked and swept.
-    pub fn run_full_cycle(&self, root: &impl Collect) {
-        // Only need to reset phase if there are objects to be swept.
-        // Resetting to sleep phase is always safe.
-        if !self.newly_allocated.borrow().is_empty() {
-            self.phase.set(CollectionPhase::Sleep);
+    /// Marks every object reachable from the root, returning the address of each object as it
+    /// was marked.
+    ///
+    /// This restarts the marking of any in progress cycle, which is always safe, as unreachable
+    /// objects are only ever freed during the sweep phase.
+    #[cfg(debug_assertions)]
+    pub fn trace_report(&self, root: &impl Collect) -> Vec<*const ()> {
+        *self.trace_log.borrow_mut() = Some(Vec::new());
+        self.remark(root);
+        self.trace_log.borrow_mut().take().unwrap_or_default()
+    }
+
+    /// Marks every object reachable from the root, returning the address and type name of each
+    /// object which would be freed by the next sweep.
+    ///
+    /// As with [`Context::trace_report`], this restarts the marking of any in progress cycle.
+    pub fn unreachable_objects(&self, root: &impl Collect) -> Vec<(*const (), &'static str)> {
+        self.remark(root);
+
+        self.objects
+            .borrow()
+            .iter()
+            .filter(|obj| obj.colour() == Colour::White)
+            .map(|obj| (obj.data_ptr().cast_const().cast(), obj.vtable().type_name()))
+            .collect()
+    }
+
+    /// Clears the marks of every object, and then marks everything reachable from the root,
+    /// without advancing the phase, or queueing any finalizers.
+    fn remark(&self, root: &impl Collect) {
+        for obj in self.objects.borrow().iter() {
+            unsafe { obj.set_colour(Colour::White) };
+            obj.set_regray_list(self.regray_list());
         }
+        self.clear_gray();
+
+        self.set_root_untraced();
+        while self.trace_next_reachable(root).is_some() {}
+    }
+
+    pub fn swept(&self) -> usize {
+        self.swept.get()
+    }
+
+    /// Frees every object after the first `len`, once the root has been restored to `root`.
+    ///
+    /// Any collection in progress is abandoned, and the values of the freed objects are dropped
+    /// without running their finalizers, even if they had already been queued.
+    ///
+    /// # Panics
+    /// If any of the objects to be freed is still reachable from `root`, including through a
+    /// [`Weak`](crate::Weak), from a [`Rooted`](crate::Rooted) handle, or from an older object
+    /// waiting to be finalized.
+    ///
+    /// # Safety
+    /// No objects may have been swept since there were `len` objects.
+    pub unsafe fn truncate(&self, root: &impl Collect, len: usize) {
+        let tail: std::collections::HashSet<_> = self.objects.borrow()[len..]
+            .iter()
+            .map(|obj| obj.into_raw())
+            .collect();
+        let in_tail = |obj: &GcBox<Erased>| tail.contains(&obj.into_raw());
+
+        // Objects which are about to be freed don't keep anything alive by waiting to be
+        // finalized.
+        let queue = self.finalization_queue.take();
+        *self.finalization_queue.borrow_mut() =
+            queue.iter().copied().filter(|obj| !in_tail(obj)).collect();
+
+        self.remark(root);
+        // The marks are of `root`, which doesn't have to be the root the cycle was started with.
+        self.phase.set(CollectionPhase::Sleep);
+
+        let objects = self.objects.borrow_mut().split_off(len);
+        if let Some(obj) = objects.iter().find(|obj| obj.colour() != Colour::White) {
+            let name = obj.vtable().type_name();
+            self.objects.borrow_mut().extend(objects);
+            *self.finalization_queue.borrow_mut() = queue;
+            panic!("a `{name}` allocated since the checkpoint is still reachable");
+        }
+
+        self.finalizable.borrow_mut().retain(|obj| !in_tail(obj));
 
-        if self.phase.get() == CollectionPhase::Sleep {
-            self.advance_phase();
+        for obj in objects.iter() {
+            unsafe { obj.drop_in_place() };
         }
 
+        for obj in objects {
+            unsafe { self.deallocate(obj) };
+        }
+    }
+
+    /// The position of the collector within the current cycle.
+    pub fn cursor(&self) -> CollectionCursor {
+        let phase = match self.phase.get() {
+            CollectionPhase::Sleep => CursorPhase::Sleep,
+            CollectionPhase::Mark => CursorPhase::Mark {
+                gray: self.gray.len(),
+            },
+            CollectionPhase::Sweep { index } => CursorPhase::Sweep { index },
+        };
+
+        CollectionCursor {
+            cycle: self.cycles.get(),
+            phase,
+        }
+    }
+
+    /// Runs the collection until the given cycle has finished, picking up from wherever the
+    /// current one is, and starting it if the collector is asleep.
+    pub fn finish_cycle(&self, root: &impl Collect, cycle: usize) {
+        while self.cycles.get() <= cycle {
+            if self.phase.get() == CollectionPhase::Sleep {
+                self.advance_phase();
+            } else {
+                self.advance_cycle_by(root, Pacing::MAX_PACE);
+            }
+        }
+    }
+
+    /// Runs the collection cycle until all allocated objects have been marked and swept.
+    pub fn run_full_cycle(&self, root: &impl Collect) {
+        // Restarting from the sleep phase is always safe, and means that everything which was
+        // unreachable beforehand is freed, even if it was allocated during the current cycle.
+        self.phase.set(CollectionPhase::Sleep);
+        self.advance_phase();
+
         while self.phase.get() != CollectionPhase::Sleep {
-            dbg!(self.phase.get());
             self.advance_cycle_by(root, Pacing::MAX_PACE);
         }
 
-        debug_assert!(matches!(self.phase.get(), CollectionPhase::Sleep { .. }));
+        debug_assert!(matches!(self.phase.get(), CollectionPhase::Sleep));
     }
 }
 
 impl<A: Allocator + ?Sized> Context<A> {
+    /// Empties the gray list and the list of re-grayed objects, without changing the colour of
+    /// any object.
+    fn clear_gray(&self) {
+        self.gray.clear();
+        self.regrayed.clear();
+    }
+
+    fn regray_list(&self) -> NonNull<GrayList> {
+        NonNull::from(&*self.regrayed)
+    }
+
+    pub fn allocation_pressure(&self) -> f32 {
+        self.pacing
+            .pressure(self.cycle_allocations.get(), self.cycle_bytes.get())
+    }
+
+    pub fn set_root_untraced(&self) {
+        self.trace_root.set(true);
+    }
+
+    pub fn set_root_traced(&self) {
+        self.trace_root.set(false);
+    }
+
+    pub fn explicit_roots(&self) -> &RootSet {
+        &self.explicit_roots
+    }
+
+    /// Sets the finalizer of the object, which is queued once the object is found to be
+    /// unreachable.
+    ///
+    /// # Safety
+    /// `T` must be the type of the value in the box.
+    pub unsafe fn set_finalizer<'b, T: Collect + Finalize<'b>>(&self, gc: GcBox<T>) {
+        unsafe { gc.set_finalizer() };
+        self.finalizable.borrow_mut().push(gc.erase());
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn contains(&self, ptr: NonNull<()>) -> bool {
+        self.addresses.borrow().contains(&ptr.addr().get())
+    }
+
+    /// Marks the box as reachable, adding it to the gray list if it hasn't already been reached
+    /// this cycle.
+    pub fn mark_box<T: ?Sized>(&self, ptr: GcBox<T>) {
+        match ptr.colour() {
+            Colour::White | Colour::Weak => {
+                unsafe { ptr.set_colour(Colour::Gray) };
+
+                #[cfg(debug_assertions)]
+                if let Some(log) = &mut *self.trace_log.borrow_mut() {
+                    log.push(ptr.data_ptr().cast_const().cast());
+                }
+
+                self.push_box(ptr.erase());
+            }
+            Colour::Gray | Colour::Black => {}
+        }
+    }
+
     pub fn push_box(&self, ptr: GcBox<Erased>) {
-        ptr.set_next(self.first_gray.get());
-        self.first_gray.set(Some(ptr));
+        self.gray.push(ptr);
     }
 
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub fn allocate<T: ?Sized + Collect + Pointee>(
         &self,
         meta: T::Metadata,
         layout: Layout,
     ) -> GcBox<T> {
-        let Ok(layout) = GcInner::<T>::layout(layout) else {
-            todo!()
-        };
+        match self.try_allocate(meta, layout) {
+            Ok(gc) => gc,
+            Err(_) => match GcInner::<T>::layout(layout) {
+                Ok(layout) => alloc::alloc::handle_alloc_error(layout),
+                Err(_) => panic!("allocation too large"),
+            },
+        }
+    }
 
-        let ptr = self.alloc.allocate(layout).unwrap();
+    /// Allocates a box for a value with the given layout, returning an error if the layout of
+    /// the box would overflow, or if the allocator fails.
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn try_allocate<T: ?Sized + Collect + Pointee>(
+        &self,
+        meta: T::Metadata,
+        layout: Layout,
+    ) -> Result<GcBox<T>, AllocError> {
+        self.try_allocate_with::<T, T::Metadata>(meta, layout)
+    }
 
-        let gc = unsafe { GcBox::new(ptr.as_ptr().cast(), meta, layout) };
+    /// Allocates a box for a `T` with room for pointer-sized metadata, which is the size of the
+    /// metadata of slices and trait objects, so that it can be given an unsized form in place,
+    /// with [`GcBox::unsize`].
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn allocate_unsizable<T: Collect>(&self) -> GcBox<T> {
+        let layout = Layout::new::<T>();
+        match self.try_allocate_with::<T, usize>((), layout) {
+            Ok(gc) => gc,
+            Err(_) => match GcInner::<T, usize>::layout(layout) {
+                Ok(layout) => alloc::alloc::handle_alloc_error(layout),
+                Err(_) => panic!("allocation too large"),
+            },
+        }
+    }
 
+    /// Allocates a box as with [`Context::try_allocate`], with room for metadata of type `M`.
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    fn try_allocate_with<T: ?Sized + Collect + Pointee, M>(
+        &self,
+        meta: T::Metadata,
+        layout: Layout,
+    ) -> Result<GcBox<T>, AllocError> {
+        let gc = self.allocate_untracked::<T, M>(meta, layout)?;
+        #[cfg(feature = "debug-alloc-tags")]
+        gc.set_site(Location::caller());
         self.objects.borrow_mut().push(gc.erase());
+        Ok(gc)
+    }
+
+    /// Allocates a box for each of the values, only borrowing the list of objects once.
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn allocate_batch<T: Collect>(&self, values: Vec<T>) -> Vec<GcBox<T>> {
+        let objects = &mut *self.objects.borrow_mut();
+        objects.reserve(values.len());
+        #[cfg(feature = "debug-alloc-tags")]
+        let site = Location::caller();
+
+        values
+            .into_iter()
+            .map(|val| {
+                let gc = match self.allocate_untracked::<T, ()>((), Layout::new::<T>()) {
+                    Ok(gc) => gc,
+                    Err(_) => alloc::alloc::handle_alloc_error(
+                        GcInner::<T>::layout(Layout::new::<T>()).unwrap(),
+                    ),
+                };
+                // Safety: No references exist, as the pointer was just created.
+                unsafe { gc.data_ptr().write(val) };
+                // Safety: The value was just written.
+                unsafe { gc.set_init() };
+                #[cfg(feature = "debug-alloc-tags")]
+                gc.set_site(site);
+
+                objects.push(gc.erase());
+                gc
+            })
+            .collect()
+    }
+
+    /// Allocates a box as with [`Context::try_allocate_with`], without adding it to the list of
+    /// objects.
+    fn allocate_untracked<T: ?Sized + Collect + Pointee, M>(
+        &self,
+        meta: T::Metadata,
+        layout: Layout,
+    ) -> Result<GcBox<T>, AllocError> {
+        let value_layout = layout;
+        let layout = GcInner::<T, M>::layout(value_layout).map_err(|_| AllocError)?;
+
+        let ptr = self.alloc.allocate(layout)?;
+
+        // Safety: The regray list lives as long as the context, which outlives its objects.
+        let gc: GcBox<T> =
+            unsafe { GcBox::new(ptr.as_ptr().cast(), meta, layout, self.regray_list()) };
+        debug_assert_eq!(gc.data_ptr().addr() % value_layout.align(), 0);
+
+        // See `CollectionPhase` for the colour of newly allocated objects.
+        match self.phase.get() {
+            CollectionPhase::Sleep => {}
+            CollectionPhase::Mark => {
+                unsafe { gc.set_colour(Colour::Gray) };
+                self.push_box(gc.erase());
+                self.debt.set(self.debt.get() + 1);
+            }
+            CollectionPhase::Sweep { .. } => {
+                unsafe { gc.set_colour(Colour::Black) };
+                self.debt.set(self.debt.get() + 1);
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        self.addresses
+            .borrow_mut()
+            .insert(gc.into_raw().addr().get());
 
-        gc
+        self.cycle_allocations.set(self.cycle_allocations.get() + 1);
+        self.cycle_bytes.set(self.cycle_bytes.get() + layout.size());
+        self.live_bytes.set(self.live_bytes.get() + layout.size());
+
+        Ok(gc)
     }
 
     pub unsafe fn deallocate(&self, gc: GcBox<Erased>) {
         let layout = gc.layout();
 
         let ptr = gc.inner_ptr().cast::<u8>();
+        #[cfg(debug_assertions)]
+        assert!(
+            self.addresses.borrow_mut().remove(&ptr.addr()),
+            "object freed at {ptr:p}, which isn't the address it was allocated at"
+        );
 
         unsafe { self.alloc.deallocate(NonNull::new_unchecked(ptr), layout) };
+        self.live_bytes.set(self.live_bytes.get() - layout.size());
     }
 }
 
@@ -306,13 +959,35 @@ where
     A: Allocator + ?Sized,
 {
     fn drop(&mut self) {
-        let newly_allocated: &[GcBox<Erased>] = &self.newly_allocated.borrow();
         let objects: &[GcBox<Erased>] = &self.objects.borrow();
 
-        for obj in objects.iter().chain(newly_allocated) {
-            unsafe { obj.vtable().drop_in_place(*obj) };
+        #[cfg(debug_assertions)]
+        let leaked: usize = objects.iter().map(|obj| obj.raw_handles()).sum();
 
-            unsafe { alloc::alloc::dealloc(obj.inner_ptr().cast::<u8>(), obj.layout()) };
+        // Every value is dropped before any storage is freed, so that destructors can still
+        // safely upgrade weak pointers to the other objects. Values which were already dropped,
+        // because they were only weakly reachable, are skipped.
+        for obj in objects.iter() {
+            unsafe { obj.drop_in_place() };
+        }
+
+        for obj in objects.iter() {
+            unsafe { self.deallocate(*obj) };
+        }
+
+        // Panicking again while unwinding would abort, hiding the original panic.
+        #[cfg(debug_assertions)]
+        if leaked > 0 && !std::thread::panicking() {
+            match leaked {
+                1 => panic!(
+                    "1 pointer returned by `Gc::into_raw` was never passed back to \
+                     `Gc::from_raw` before the arena was dropped"
+                ),
+                _ => panic!(
+                    "{leaked} pointers returned by `Gc::into_raw` were never passed back to \
+                     `Gc::from_raw` before the arena was dropped"
+                ),
+            }
         }
     }
 }
@@ -323,9 +998,27 @@ pub struct Pacing {
     pub trigger_allocations: Option<usize>,
     pub mark_stride: usize,
     pub sweep_stride: usize,
+    /// Whether collections are only ever started explicitly, such as by
+    /// [`Arena::complete_collection`], in which case the triggers are ignored.
+    ///
+    /// A pacing which has neither trigger must set this, so that a pacing which accidentally
+    /// never starts a collection is caught when the arena is created. See [`Pacing::MANUAL`].
+    ///
+    /// [`Arena::complete_collection`]: crate::Arena::complete_collection
+    pub manual: bool,
 }
 
 impl Pacing {
+    /// A pacing which never starts a collection by itself, so that the arena is only collected
+    /// when asked to be.
+    pub const MANUAL: Pacing = Pacing {
+        trigger_bytes: None,
+        trigger_allocations: None,
+        mark_stride: 16,
+        sweep_stride: 8,
+        manual: true,
+    };
+
     /// The maximum possible pace for the garbage collector to run. It will always trigger, and
     /// never stop tracing.
     const MAX_PACE: Pacing = Pacing {
@@ -333,11 +1026,38 @@ impl Pacing {
         trigger_allocations: Some(0),
         mark_stride: usize::MAX,
         sweep_stride: usize::MAX,
+        manual: false,
     };
 
+    /// # Panics
+    /// If the pacing never starts a collection, without being marked as manual.
+    pub(crate) fn validate(&self) {
+        assert!(
+            self.manual || self.trigger_bytes.is_some() || self.trigger_allocations.is_some(),
+            "pacing has no trigger, so no collection would ever start; use `Pacing::MANUAL` to \
+             only collect explicitly"
+        );
+    }
+
     fn should_wake(&self, allocations: usize, bytes: usize) -> bool {
-        self.trigger_allocations.is_some_and(|n| allocations >= n)
-            || self.trigger_bytes.is_some_and(|n| bytes >= n)
+        !self.manual
+            && (self.trigger_allocations.is_some_and(|n| allocations >= n)
+                || self.trigger_bytes.is_some_and(|n| bytes >= n))
+    }
+
+    /// How close the counters are to the nearest trigger, where `1.0` is exactly at it.
+    fn pressure(&self, allocations: usize, bytes: usize) -> f32 {
+        if self.manual {
+            return 0.0;
+        }
+
+        let ratio = |count: usize, trigger: Option<usize>| match trigger {
+            None => 0.0,
+            Some(0) => 1.0,
+            Some(n) => count as f32 / n as f32,
+        };
+
+        ratio(allocations, self.trigger_allocations).max(ratio(bytes, self.trigger_bytes))
     }
 }
 
@@ -348,16 +1068,214 @@ impl Default for Pacing {
             trigger_allocations: Some(64),
             mark_stride: 16,
             sweep_stride: 8,
+            manual: false,
         }
     }
 }
 
+/// Abandons the current cycle if a `Collect::trace` implementation panics while marking, so that
+/// the gray list isn't left partially consumed, and the next cycle starts afresh.
+struct AbortCycleOnUnwind<'a, A: Allocator + ?Sized>(&'a Context<A>);
+
+impl<A: Allocator + ?Sized> Drop for AbortCycleOnUnwind<'_, A> {
+    fn drop(&mut self) {
+        self.0.clear_gray();
+        self.0.phase.set(CollectionPhase::Sleep);
+    }
+}
+
+/// The phase of the current collection cycle.
+///
+/// Every object is kept in `Context::objects`, and the colour it is given when allocated depends
+/// on the phase, so that no reachable object is ever swept:
+///
+/// - While sleeping, objects are white, as every object is made white when marking starts.
+/// - While marking, objects are gray, so that they survive the cycle, and have their contents
+///   traced before marking ends.
+/// - While sweeping, objects are black, as they are only swept when white, and the sweep can't
+///   know whether they're reachable.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum CollectionPhase {
     #[default]
     Sleep,
     Mark,
+    /// Objects before `index` have already been swept.
     Sweep {
         index: usize,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::Global;
+
+    use super::{CollectionPhase, Context, Mutation, Pacing};
+    use crate::{
+        gc_box::Colour,
+        locked::{LockedRefCell, Unlock},
+        Collect, Collector, Finalize, Gc,
+    };
+
+    #[test]
+    fn with_capacity_does_not_reallocate() {
+        let ctx = Context::with_capacity_in(Pacing::default(), Global, 10_000);
+        let mt = Mutation::new(&ctx);
+
+        let capacity = ctx.objects.borrow().capacity();
+        assert!(capacity >= 10_000);
+
+        for i in 0..10_000 {
+            let _ = Gc::new(i, mt);
+        }
+
+        assert_eq!(ctx.objects.borrow().capacity(), capacity);
+        assert_eq!(ctx.allocations(), 10_000);
+    }
+
+    #[test]
+    fn mark_terminates_under_allocation() {
+        let pacing = Pacing {
+            trigger_allocations: Some(0),
+            mark_stride: 1,
+            ..Pacing::default()
+        };
+        let ctx = Context::with_capacity_in(pacing, Global, 0);
+        let mt = Mutation::new(&ctx);
+        let root = LockedRefCell::new(Vec::new());
+
+        for i in 0..100 {
+            unsafe { root.unlock_unchecked() }
+                .borrow_mut()
+                .push(Gc::new(i, mt));
+        }
+
+        ctx.advance_collection(&root);
+        assert_eq!(ctx.phase.get(), CollectionPhase::Mark);
+
+        let mut steps = 0;
+        while ctx.phase.get() == CollectionPhase::Mark {
+            for i in 0..5 {
+                let _garbage = Gc::new(i, mt);
+            }
+            unsafe { root.unlock_unchecked() }
+                .borrow_mut()
+                .push(Gc::new(0, mt));
+            ctx.set_root_untraced();
+
+            ctx.advance_collection(&root);
+            steps += 1;
+            assert!(steps <= 200, "marking didn't finish");
+        }
+
+        ctx.run_full_cycle(&root);
+        assert_eq!(ctx.allocations(), 100 + steps);
+    }
+
+    #[test]
+    fn write_barrier_only_regrays_written_objects() {
+        let pacing = Pacing {
+            trigger_allocations: Some(0),
+            mark_stride: 1,
+            ..Pacing::default()
+        };
+        let ctx = Context::with_capacity_in(pacing, Global, 0);
+        let mt = Mutation::new(&ctx);
+        let root = LockedRefCell::new((0..100).map(|i| Gc::new(i, mt)).collect::<Vec<_>>());
+
+        // Start marking, and trace the root and some of the objects.
+        for _ in 0..20 {
+            ctx.advance_collection(&root);
+        }
+        assert_eq!(ctx.phase.get(), CollectionPhase::Mark);
+
+        let black: Vec<_> = root
+            .borrow()
+            .iter()
+            .copied()
+            .filter(|gc| gc.into_box().colour() == Colour::Black)
+            .collect();
+        assert!(!black.is_empty());
+
+        for gc in &black {
+            gc.write();
+        }
+        assert_eq!(ctx.regrayed.len(), black.len());
+
+        while ctx.phase.get() == CollectionPhase::Mark {
+            ctx.advance_collection(&root);
+        }
+        assert!(ctx.regrayed.is_empty());
+
+        ctx.run_full_cycle(&root);
+        assert_eq!(ctx.allocations(), 100);
+    }
+
+    struct Finalized;
+
+    unsafe impl Collect for Finalized {
+        const NEEDS_TRACE: bool = false;
+
+        fn trace(&self, _c: &Collector) {}
+    }
+
+    impl Finalize<'_> for Finalized {
+        fn finalize(&self, _mt: &Mutation<'_>) {}
+    }
+
+    #[test]
+    fn only_finalizable_objects_are_checked() {
+        let ctx = Context::with_capacity_in(Pacing::default(), Global, 0);
+        let mt = Mutation::new(&ctx);
+        let root = LockedRefCell::new(Vec::new());
+
+        for i in 0..100 {
+            let _garbage = Gc::new(i, mt);
+        }
+        for _ in 0..3 {
+            let _ = Gc::new_finalized(Finalized, mt);
+        }
+        let kept = Gc::new_finalized(Finalized, mt);
+        unsafe { root.unlock_unchecked() }.borrow_mut().push(kept);
+        assert_eq!(ctx.finalizable.borrow().len(), 4);
+
+        ctx.run_full_cycle(&root);
+        assert_eq!(ctx.finalizable.borrow().len(), 1);
+        assert_eq!(ctx.pending_finalizers(), 3);
+    }
+
+    #[test]
+    fn allocated_during_sweep_survives() {
+        let pacing = Pacing {
+            trigger_allocations: Some(0),
+            sweep_stride: 1,
+            ..Pacing::default()
+        };
+        let ctx = Context::with_capacity_in(pacing, Global, 0);
+        let mt = Mutation::new(&ctx);
+        let root = LockedRefCell::new(Vec::new());
+
+        for i in 0..20 {
+            let _garbage = Gc::new(i, mt);
+        }
+
+        while !matches!(ctx.phase.get(), CollectionPhase::Sweep { .. }) {
+            ctx.advance_collection(&root);
+        }
+
+        ctx.advance_collection(&root);
+        for i in 0..10 {
+            unsafe { root.unlock_unchecked() }
+                .borrow_mut()
+                .push(Gc::new(i, mt));
+            ctx.set_root_untraced();
+            ctx.advance_collection(&root);
+        }
+
+        while ctx.phase.get() != CollectionPhase::Sleep {
+            ctx.advance_collection(&root);
+        }
+
+        assert_eq!(ctx.allocations(), 10);
+        assert!(root.borrow().iter().map(|gc| **gc).eq(0..10));
+    }
+}
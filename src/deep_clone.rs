@@ -0,0 +1,158 @@
+use core::alloc::Layout;
+use std::{alloc::Allocator, cell::RefCell, collections::HashMap};
+
+use crate::{
+    context::Context,
+    gc_box::{Erased, GcBox},
+    locked::{LockedCell, LockedRefCell},
+    Collect, Gc,
+};
+
+/// A value which can be copied, along with every garbage collected object reachable from it,
+/// into another arena. Used by the [`Clone`] implementation of [`Arena`].
+///
+/// # Safety
+/// The implementation must construct the copy only from the results of calling `deep_clone` on
+/// its fields, and must not dereference any garbage collected pointer returned by `deep_clone`,
+/// as the object it points to may still be in the middle of being copied.
+///
+/// [`Arena`]: crate::Arena
+pub unsafe trait DeepClone {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self;
+}
+
+/// The state of an in progress [`DeepClone`], mapping each copied object to its copy, so that
+/// shared and cyclic references are preserved.
+pub struct DeepCloner<'a> {
+    context: &'a Context<dyn Allocator>,
+    copies: RefCell<HashMap<*const (), GcBox<Erased>>>,
+}
+
+impl<'a> DeepCloner<'a> {
+    pub(crate) fn new(context: &'a Context<dyn Allocator>) -> DeepCloner<'a> {
+        DeepCloner {
+            context,
+            copies: Default::default(),
+        }
+    }
+}
+
+impl DeepCloner<'_> {
+    /// Copies an object through a pointer to any of its forms, such as a `Gc<dyn Trait>`, using
+    /// the [`DeepClone`] implementation of the type it was allocated as.
+    ///
+    /// # Panics
+    /// If the object wasn't allocated with [`UniqueGc::new_cloneable`].
+    ///
+    /// [`UniqueGc::new_cloneable`]: crate::UniqueGc::new_cloneable
+    pub fn clone_unsized<'b, T: ?Sized>(&self, gc: Gc<'b, T>) -> Gc<'b, T> {
+        let metadata = core::ptr::metadata(gc.as_ptr());
+        let gc = gc.into_box();
+
+        // Safety: The vtable belongs to the box, whose value is reachable, and so initialized.
+        let copy = unsafe { gc.vtable().deep_clone(gc.erase(), self) };
+        // Safety: The copy has the same type as the original, so it is valid with the same
+        // metadata, and it has room for the metadata, as the original does. An object only ever
+        // has a single unsized form at a time, so this never overwrites different metadata.
+        unsafe { Gc::from_box(copy.unsize::<T>(metadata)) }
+    }
+}
+
+impl std::fmt::Debug for DeepCloner<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeepCloner")
+            .field("copies", &self.copies.borrow().len())
+            .finish()
+    }
+}
+
+unsafe impl<'b, T: DeepClone + Collect> DeepClone for Gc<'b, T> {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        let key = self.as_ptr().cast_const().cast::<()>();
+
+        if let Some(copy) = cx.copies.borrow().get(&key) {
+            return unsafe { Gc::from_box(copy.restore_type()) };
+        }
+
+        // The copy is registered before its contents, so that cycles back to it find it. It has
+        // room for metadata if the original does, so that `clone_unsized` can unsize it in place.
+        let copy = if self.into_box().is_unsizable() {
+            cx.context.allocate_unsizable::<T>()
+        } else {
+            cx.context.allocate::<T>((), Layout::new::<T>())
+        };
+        if self.into_box().vtable().can_deep_clone() {
+            // Safety: The box was allocated for a `T`.
+            unsafe { copy.set_cloneable() };
+        }
+        cx.copies.borrow_mut().insert(key, copy.erase());
+
+        let value = (**self).deep_clone(cx);
+        unsafe { copy.data_ptr().write(value) };
+        unsafe { copy.set_init() };
+
+        unsafe { Gc::from_box(copy) }
+    }
+}
+
+macro_rules! deep_clone_leaf {
+    ($($t:ty),*) => {
+        $(
+            unsafe impl DeepClone for $t {
+                fn deep_clone(&self, _: &DeepCloner<'_>) -> Self {
+                    self.clone()
+                }
+            }
+        )*
+    };
+}
+
+deep_clone_leaf!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    bool,
+    (),
+    f32,
+    f64,
+    String
+);
+
+unsafe impl<T: DeepClone> DeepClone for Option<T> {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        self.as_ref().map(|v| v.deep_clone(cx))
+    }
+}
+
+unsafe impl<T: DeepClone> DeepClone for Vec<T> {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        self.iter().map(|v| v.deep_clone(cx)).collect()
+    }
+}
+
+unsafe impl<T: DeepClone> DeepClone for Box<T> {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        Box::new((**self).deep_clone(cx))
+    }
+}
+
+unsafe impl<T: DeepClone + Copy> DeepClone for LockedCell<T> {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        LockedCell::new(self.get().deep_clone(cx))
+    }
+}
+
+unsafe impl<T: DeepClone> DeepClone for LockedRefCell<T> {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        LockedRefCell::new(self.borrow().deep_clone(cx))
+    }
+}
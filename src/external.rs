@@ -0,0 +1,73 @@
+use core::ops::{Deref, DerefMut};
+use std::fmt::Debug;
+
+use crate::{Collect, Collector};
+
+/// A wrapper which makes any `'static` value a leaf, so that it can be stored in garbage
+/// collected objects.
+///
+/// This is intended for state which is shared with code outside of the arena, such as an
+/// `Rc<RefCell<T>>`. Every garbage collected pointer is branded with the lifetime of its arena,
+/// so the `'static` bound on the whole value, including anything behind the `RefCell`, guarantees
+/// that it can't hide a pointer from the collector.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, External, Gc};
+/// # use std::{cell::RefCell, rc::Rc};
+/// # once_arena(|mt| {
+/// let shared = Rc::new(RefCell::new(Vec::new()));
+/// let gc = Gc::new(External::new(shared.clone()), mt);
+///
+/// shared.borrow_mut().push(1u8);
+/// assert_eq!(*gc.borrow(), [1]);
+/// # });
+/// ```
+///
+/// ```compile_fail
+/// # use ghost_gc::{once_arena, External, Gc};
+/// # use std::{cell::RefCell, rc::Rc};
+/// # once_arena(|mt| {
+/// let hidden = Rc::new(RefCell::new(Gc::new(5, mt)));
+/// let gc = Gc::new(External::new(hidden), mt);
+/// # });
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct External<T: ?Sized + 'static>(T);
+
+impl<T: 'static> External<T> {
+    pub const fn new(value: T) -> External<T> {
+        External(value)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: ?Sized + 'static> Deref for External<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: ?Sized + 'static> DerefMut for External<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: ?Sized + Debug + 'static> Debug for External<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("External").field(&&self.0).finish()
+    }
+}
+
+unsafe impl<T: ?Sized + 'static> Collect for External<T> {
+    const NEEDS_TRACE: bool = false;
+
+    fn trace(&self, _: &Collector) {}
+}
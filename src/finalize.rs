@@ -0,0 +1,24 @@
+use crate::Mutation;
+
+/// A type which needs to run some code with access to its arena, once it becomes unreachable.
+///
+/// Values are only finalized if they were allocated with [`Gc::new_finalized`]. Once such a
+/// value is found to be unreachable, it and everything it can reach are kept alive, and it is
+/// added to the arena's finalization queue. Calling [`Arena::run_finalizers`] then runs each
+/// queued finalizer, after which the value is freed by a later collection, once it is
+/// unreachable again.
+///
+/// A value is only ever finalized once. Any value which hasn't been finalized by the time its
+/// arena is dropped, whether or not it is reachable, is finalized then, before any value in the
+/// arena is dropped. Unlike finalizers, [`Drop`] implementations run in an arbitrary order during
+/// teardown, so they must not dereference any [`Gc`], as the value it points to may already have
+/// been dropped. They can instead upgrade a [`Weak`], which returns `None` once its value has
+/// been dropped.
+///
+/// [`Gc`]: crate::Gc
+/// [`Weak`]: crate::Weak
+/// [`Gc::new_finalized`]: crate::Gc::new_finalized
+/// [`Arena::run_finalizers`]: crate::Arena::run_finalizers
+pub trait Finalize<'b> {
+    fn finalize(&self, mt: &Mutation<'b>);
+}
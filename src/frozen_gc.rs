@@ -0,0 +1,179 @@
+use core::{hash::Hash, ops::Deref};
+use std::fmt::Debug;
+
+use crate::{Collect, Collector, Gc};
+
+/// A wrapper around a [`Gc`] for use as a key in a hashed or ordered collection, which checks in
+/// debug builds that the value it points to isn't modified while it is a key.
+///
+/// A [`Gc`] is compared and hashed by the value it points to, and that value can be modified
+/// through a lock, such as a [`LockedCell`](crate::locked::LockedCell), even while the pointer is
+/// a key in a [`HashMap`](std::collections::HashMap). The map then looks for the key in the wrong
+/// place, and lookups and removals of it silently fail. A `FrozenGc` records the hash of its
+/// value when it is created, and panics when it is hashed or compared after the hash has
+/// changed. In release builds it is equivalent to the `Gc` it wraps.
+///
+/// See [`ByAddress`](crate::ByAddress) for keys compared by identity, which are unaffected by
+/// modifications.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, FrozenGc, Gc};
+/// # use std::collections::HashMap;
+/// # once_arena(|mt| {
+/// let mut ids = HashMap::new();
+/// ids.insert(FrozenGc::new(Gc::from_str("x", mt)), 0);
+/// ids.insert(FrozenGc::new(Gc::from_str("y", mt)), 1);
+///
+/// assert_eq!(ids.get("y"), Some(&1));
+/// # });
+/// ```
+pub struct FrozenGc<'b, T: ?Sized> {
+    gc: Gc<'b, T>,
+    #[cfg(debug_assertions)]
+    fingerprint: u64,
+}
+
+impl<'b, T: ?Sized + Hash> FrozenGc<'b, T> {
+    pub fn new(gc: Gc<'b, T>) -> FrozenGc<'b, T> {
+        FrozenGc {
+            gc,
+            #[cfg(debug_assertions)]
+            fingerprint: fingerprint(&*gc),
+        }
+    }
+
+    /// # Panics
+    /// In debug builds, if the value has been modified since the `FrozenGc` was created.
+    fn check(&self) {
+        #[cfg(debug_assertions)]
+        assert!(
+            fingerprint(&*self.gc) == self.fingerprint,
+            "the value of a `FrozenGc` was modified while it was a key"
+        );
+    }
+}
+
+impl<'b, T: ?Sized> FrozenGc<'b, T> {
+    pub fn gc(&self) -> Gc<'b, T> {
+        self.gc
+    }
+}
+
+#[cfg(debug_assertions)]
+fn fingerprint<T: ?Sized + Hash>(value: &T) -> u64 {
+    use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+
+    BuildHasherDefault::<DefaultHasher>::default().hash_one(value)
+}
+
+impl<T: ?Sized> Deref for FrozenGc<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.gc
+    }
+}
+
+impl<T: ?Sized> core::borrow::Borrow<T> for FrozenGc<'_, T> {
+    fn borrow(&self) -> &T {
+        &self.gc
+    }
+}
+
+unsafe impl<T: ?Sized> Collect for FrozenGc<'_, T> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.gc.trace(c);
+    }
+}
+
+impl<T: ?Sized> Clone for FrozenGc<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for FrozenGc<'_, T> {}
+
+impl<T: ?Sized + Debug> Debug for FrozenGc<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("FrozenGc").field(&&*self.gc).finish()
+    }
+}
+
+impl<T: ?Sized + Hash + PartialEq> PartialEq for FrozenGc<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.check();
+        other.check();
+        self.gc == other.gc
+    }
+}
+
+impl<T: ?Sized + Hash + Eq> Eq for FrozenGc<'_, T> {}
+
+impl<T: ?Sized + Hash> Hash for FrozenGc<'_, T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.check();
+        self.gc.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, hash::Hash};
+
+    use crate::{locked::LockedCell, once_arena, Collect, Collector, FrozenGc, Gc};
+
+    struct Key(LockedCell<u32>);
+
+    impl PartialEq for Key {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.get() == other.0.get()
+        }
+    }
+
+    impl Eq for Key {}
+
+    impl Hash for Key {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            self.0.get().hash(state);
+        }
+    }
+
+    unsafe impl Collect for Key {
+        const NEEDS_TRACE: bool = false;
+
+        fn trace(&self, _c: &Collector) {}
+    }
+
+    #[test]
+    fn unmodified_keys() {
+        once_arena(|mt| {
+            let a = FrozenGc::new(Gc::new(Key(LockedCell::new(1)), mt));
+            let b = FrozenGc::new(Gc::new(Key(LockedCell::new(2)), mt));
+
+            let map = HashMap::from([(a, "a"), (b, "b")]);
+            assert_eq!(map.get(&Key(LockedCell::new(2))), Some(&"b"));
+            assert_eq!(map[&a], "a");
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "was modified while it was a key"]
+    fn modified_key() {
+        once_arena(|mt| {
+            let key = Gc::new(Key(LockedCell::new(1)), mt);
+            let mut map = HashMap::from([(FrozenGc::new(key), ())]);
+
+            key.write().project(|k| &k.0).unlock().set(2);
+
+            // Growing the map rehashes the modified key.
+            for i in 3..100 {
+                map.insert(FrozenGc::new(Gc::new(Key(LockedCell::new(i)), mt)), ());
+            }
+        });
+    }
+}
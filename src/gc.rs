@@ -1,11 +1,21 @@
-use core::ops::Deref;
-use std::{fmt::Debug, hash::Hash};
+use core::{any::Any, ops::Deref, pin::Pin, ptr::NonNull};
+use std::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+    hash::Hash,
+};
 
 use crate::{
-    context::Mutation, gc_box::GcBox, locked::Unlock, Collect, Invariant, UniqueGc, Weak, Write,
+    context::Mutation, gc_box::GcBox, locked::Unlock, Collect, Finalize, Invariant, UniqueGc, Weak,
+    Write,
 };
 
 /// A thin, copyable, garbage collected pointer type.
+///
+/// A `Gc` always points to a live allocation, so it doesn't implement [`Default`]. Optional
+/// pointers should be stored as an `Option<Gc<T>>`, often inside a
+/// [`LockedCell`](crate::locked::LockedCell), both of which default to `None`, or as a [`Weak`],
+/// which defaults to a pointer which never upgrades.
 pub struct Gc<'b, T: ?Sized>(GcBox<T>, Invariant<'b>);
 
 impl<'b, T: Collect> Gc<'b, T> {
@@ -15,20 +25,118 @@ impl<'b, T: Collect> Gc<'b, T> {
     /// This allocates regardless of if `T` is zero-sized.
     ///
     /// If initialization of a more complex type is required, see [`UniqueGc`].
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub fn new(val: T, mt: &Mutation<'b>) -> Gc<'b, T> {
         let this = UniqueGc::new(val, mt);
         UniqueGc::into_gc(this)
     }
+
+    /// Allocates garbage collected memory on the heap and then places `val` into it, running
+    /// [`Finalize::finalize`] once it is found to be unreachable.
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn new_finalized(val: T, mt: &Mutation<'b>) -> Gc<'b, T>
+    where
+        T: Finalize<'b>,
+    {
+        let this = Gc::new(val, mt);
+        // Safety: The box contains a `T`.
+        unsafe { mt.context().set_finalizer(this.0) };
+        this
+    }
 }
 
 impl<'b> Gc<'b, str> {
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub fn from_str(s: &str, mt: &Mutation<'b>) -> Gc<'b, str> {
         UniqueGc::into_gc(UniqueGc::from_str(s, mt))
     }
+
+    /// Returns the length of the string, in bytes.
+    ///
+    /// This reads the length stored alongside the allocation, without dereferencing the string.
+    pub fn len(&self) -> usize {
+        self.0.metadata()
+    }
+
+    /// Returns `true` if the string has a length of zero bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Gc<'_, [T]> {
+    /// Returns the number of elements in the slice.
+    ///
+    /// This reads the length stored alongside the allocation, without dereferencing the slice.
+    pub fn len(&self) -> usize {
+        self.0.metadata()
+    }
+
+    /// Returns `true` if the slice has a length of zero.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'b, T: 'static> Gc<'b, T> {
+    /// Returns the value as a [`&dyn Any`](Any), so that generic code can find its type at
+    /// runtime.
+    ///
+    /// Only values without any garbage collected pointers are `'static`, as every `Gc` is
+    /// branded with the lifetime of its arena, so other values can't be downcast.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{once_arena, Gc};
+    /// # once_arena(|mt| {
+    /// let n = Gc::new(5i32, mt);
+    /// assert_eq!(n.as_any().downcast_ref::<i32>(), Some(&5));
+    /// assert!(!n.as_any().is::<u32>());
+    /// # });
+    /// ```
+    pub fn as_any(&self) -> &dyn Any {
+        &**self
+    }
+}
+
+impl<'b, T, const N: usize> Gc<'b, [T; N]> {
+    /// Converts a pointer to an array into a pointer to a slice of the same elements.
+    ///
+    /// The length of a slice is stored in its allocation, so the elements are only shared with
+    /// the array if it was allocated with [`UniqueGc::new_unsizable`], and are otherwise cloned
+    /// into a new slice.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{once_arena, Gc, UniqueGc};
+    /// # once_arena(|mt| {
+    /// let array = UniqueGc::into_gc(UniqueGc::new_unsizable([1, 2, 3], mt));
+    /// let slice: Gc<[i32]> = array.unsize(mt);
+    /// assert_eq!(*slice, [1, 2, 3]);
+    /// # });
+    /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn unsize(self, mt: &Mutation<'b>) -> Gc<'b, [T]>
+    where
+        T: Clone + Collect,
+    {
+        #[cfg(debug_assertions)]
+        assert!(mt.owns(&self), "pointer was allocated by a different arena");
+
+        if self.0.is_unsizable() {
+            // Safety: An array is valid as a slice of its length, and the box has room for it.
+            unsafe { Gc::from_box(self.0.unsize::<[T]>(N)) }
+        } else {
+            UniqueGc::into_gc(UniqueGc::from_slice(&*self, mt))
+        }
+    }
 }
 
 impl<'b, T: ?Sized> Gc<'b, T> {
+    /// Marks the pointed to value as modified, returning a [`Write`] which allows its interior
+    /// mutability to be unlocked.
     pub fn write(&self) -> &Write<T> {
+        self.0.write_barrier();
         unsafe { Write::new_unchecked(self) }
     }
 
@@ -43,13 +151,131 @@ impl<'b, T: ?Sized> Gc<'b, T> {
         self.0.data_ptr()
     }
 
+    /// Returns a key which totally orders objects by the address of their allocation.
+    ///
+    /// Objects are never moved by the collector, so the key of an object is stable for as long as
+    /// it is alive, and differs from that of every other live object. Keys may be reused once an
+    /// object has been freed, and aren't stable between runs of a program.
+    pub fn order_key(&self) -> usize {
+        self.0.into_raw().addr().get()
+    }
+
     pub fn downgrade(this: Gc<'b, T>) -> Weak<'b, T> {
         unsafe { Weak::from_box(this.0) }
     }
 
-    // pub(crate) fn into_box(self) -> GcBox<T> {
-    //     self.0
-    // }
+    /// Pins the pointed to value, which allows it to be used where the value must not be moved,
+    /// such as when it is a self-referential future.
+    ///
+    /// The collector never moves objects, and a `Gc` only gives shared access to its value, so
+    /// the value is always pinned. It is only dropped in place once it is unreachable, before
+    /// its memory is freed.
+    pub fn into_pin(this: Gc<'b, T>) -> Pin<Gc<'b, T>> {
+        // Safety: The value is never moved, and stays valid until it has been dropped.
+        unsafe { Pin::new_unchecked(this) }
+    }
+
+    /// Returns `true` if both pointers point to the same allocation.
+    pub fn ptr_eq(this: Gc<'b, T>, other: Gc<'b, T>) -> bool {
+        this.0.into_raw() == other.0.into_raw()
+    }
+
+    /// Consumes the `Gc`, returning an opaque pointer to its allocation.
+    ///
+    /// The pointer doesn't keep the allocation alive. It can only be turned back into a `Gc`
+    /// using [`Gc::from_raw`], for as long as the allocation is kept alive by other means, such
+    /// as being reachable from the root of the arena.
+    ///
+    /// With debug assertions enabled, collecting an object, or dropping the arena, panics if any
+    /// pointer to the object hasn't been passed back to [`Gc::from_raw`], to catch handles leaked
+    /// to foreign code.
+    pub fn into_raw(this: Gc<'b, T>) -> NonNull<()> {
+        #[cfg(debug_assertions)]
+        this.0.add_raw_handle();
+        this.0.into_raw()
+    }
+
+    /// Constructs a `Gc` from a pointer returned by [`Gc::into_raw`].
+    ///
+    /// # Safety
+    /// The pointer must have come from a call to [`Gc::into_raw`] on a `Gc<T>`, within the same
+    /// arena as `mt`. The allocation must not have been freed since, meaning that it must have
+    /// been reachable from the root of the arena at every collection since the pointer was
+    /// created.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{once_arena, Gc};
+    /// # once_arena(|mt| {
+    /// let five = Gc::new(5, mt);
+    /// let ptr = Gc::into_raw(five);
+    ///
+    /// let five_again = unsafe { Gc::<i32>::from_raw(ptr, mt) };
+    /// assert!(Gc::ptr_eq(five, five_again));
+    /// # });
+    /// ```
+    pub unsafe fn from_raw(ptr: NonNull<()>, mt: &Mutation<'b>) -> Gc<'b, T> {
+        let gc = unsafe { Gc::from_box(GcBox::from_raw(ptr)) };
+        #[cfg(debug_assertions)]
+        {
+            assert!(mt.owns(&gc), "pointer was allocated by a different arena");
+            gc.0.remove_raw_handle();
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = mt;
+        gc
+    }
+
+    /// Calls `f` with unique access to the pointed to value, so that many fields can be modified
+    /// directly, rather than each being unlocked through [`Gc::write`].
+    ///
+    /// The object is marked as modified once, for the whole batch of modifications.
+    ///
+    /// # Safety
+    /// No other pointer to the same allocation may be dereferenced, including through a
+    /// [`Weak`], until `f` returns.
+    /// If the pointer has been pinned with [`Gc::into_pin`], the value must not be moved out of.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{once_arena, Gc};
+    /// # once_arena(|mt| {
+    /// let pair = Gc::new((1, 2), mt);
+    ///
+    /// // Safety: `pair` is the only pointer to the allocation.
+    /// unsafe {
+    ///     Gc::with_unique(pair, mt, |pair| {
+    ///         pair.0 = 3;
+    ///         pair.1 = 4;
+    ///     })
+    /// };
+    /// assert_eq!(*pair, (3, 4));
+    /// # });
+    /// ```
+    pub unsafe fn with_unique<R>(
+        this: Gc<'b, T>,
+        mt: &Mutation<'b>,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> R {
+        #[cfg(debug_assertions)]
+        assert!(mt.owns(&this), "pointer was allocated by a different arena");
+        #[cfg(not(debug_assertions))]
+        let _ = mt;
+
+        // Safety: The caller guarantees that no other pointer is used while `f` runs. Only the
+        // value is lent out, so no owning pointer to it can outlive the call.
+        let mut inner = this.0;
+        let ret = f(unsafe { inner.data_mut() });
+
+        // No collection can run during `f`, so a single barrier covers every modification,
+        // including any made through interior mutability rather than `DerefMut`.
+        this.0.write_barrier();
+        ret
+    }
+
+    pub(crate) fn into_box(self) -> GcBox<T> {
+        self.0
+    }
 
     pub(crate) unsafe fn from_box(ptr: GcBox<T>) -> Gc<'b, T> {
         Gc(ptr, Invariant)
@@ -68,16 +294,28 @@ unsafe impl<'b, T: ?Sized> Collect for Gc<'b, T> {
     const NEEDS_TRACE: bool = true;
 
     fn trace(&self, c: &crate::Collector) {
-        use crate::gc_box::Colour;
+        c.context().mark_box(self.0);
+    }
+}
 
-        match self.0.colour() {
-            Colour::Gray | Colour::White | Colour::Weak => {
-                unsafe { self.0.set_colour(Colour::Gray) };
+unsafe impl<'b, T: ?Sized> Collect for Pin<Gc<'b, T>> {
+    const NEEDS_TRACE: bool = true;
 
-                c.context().push_box(self.0.erase());
-            }
-            Colour::Black => {}
-        }
+    fn trace(&self, c: &crate::Collector) {
+        // Safety: The copy of the pointer is only traced, and never used to move the value.
+        unsafe { Pin::into_inner_unchecked(*self) }.trace(c);
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for Gc<'_, T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for Gc<'_, T> {
+    fn as_ref(&self) -> &T {
+        self
     }
 }
 
@@ -95,9 +333,24 @@ impl<T: Debug + ?Sized> Debug for Gc<'_, T> {
     }
 }
 
+impl<T: Display + ?Sized> Display for Gc<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// Pointers are compared by value, consistently with their [`Hash`] implementation. See
+/// [`ByAddress`](crate::ByAddress) for comparing by identity.
+///
+/// A value which is modified through a lock while its pointer is a key in a map changes the
+/// pointer's hash, leaving the key in the wrong place. [`FrozenGc`](crate::FrozenGc) catches
+/// this in debug builds.
+///
+/// A pointer is always equal to itself without comparing the values, even if `T`'s equality
+/// isn't reflexive, as with a NaN float.
 impl<T: ?Sized + PartialEq> PartialEq for Gc<'_, T> {
     fn eq(&self, other: &Self) -> bool {
-        **self == **other
+        Gc::ptr_eq(*self, *other) || **self == **other
     }
 }
 
@@ -120,3 +373,362 @@ impl<T: ?Sized + Hash> Hash for Gc<'_, T> {
         (**self).hash(state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{once_arena, Gc, UniqueGc};
+
+    #[test]
+    fn str_len() {
+        once_arena(|mt| {
+            let s = Gc::from_str("Hello, World!", mt);
+            assert_eq!(s.len(), (*s).len());
+            assert!(!s.is_empty());
+
+            let empty = Gc::from_str("", mt);
+            assert_eq!(empty.len(), 0);
+            assert!(empty.is_empty());
+        });
+    }
+
+    #[test]
+    fn over_aligned() {
+        #[repr(align(64))]
+        struct Align64(u8);
+
+        unsafe impl crate::Collect for Align64 {
+            const NEEDS_TRACE: bool = false;
+
+            fn trace(&self, _c: &crate::Collector) {}
+        }
+
+        once_arena(|mt| {
+            for i in 0..16 {
+                let gc = Gc::new(Align64(i), mt);
+                assert_eq!(gc.as_ptr().addr() % 64, 0);
+                assert_eq!((*gc).0, i);
+            }
+
+            let slice = UniqueGc::<[Align64]>::new_uninit_slice(3, mt);
+            assert_eq!(slice.as_ptr().addr() % 64, 0);
+        });
+    }
+
+    #[test]
+    fn simd_aligned() {
+        #[derive(Clone, Copy)]
+        #[repr(C, align(32))]
+        struct F32x8([f32; 8]);
+
+        unsafe impl crate::Collect for F32x8 {
+            const NEEDS_TRACE: bool = false;
+
+            fn trace(&self, _c: &crate::Collector) {}
+        }
+
+        once_arena(|mt| {
+            for i in 0..16 {
+                // A small allocation between each, so that the vectors aren't all placed alike.
+                let _ = Gc::new(i as u8, mt);
+                let gc = Gc::new(F32x8([i as f32; 8]), mt);
+                assert_eq!(gc.as_ptr().addr() % 32, 0);
+                assert_eq!((*gc).0, [i as f32; 8]);
+            }
+
+            let slice = UniqueGc::from_slice(&[F32x8([1.0; 8]); 3], mt);
+            assert_eq!(slice.as_ptr().addr() % 32, 0);
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                use core::arch::x86_64::__m256;
+
+                let gc = Gc::new(unsafe { core::mem::zeroed::<__m256>() }, mt);
+                assert_eq!(gc.as_ptr().addr() % align_of::<__m256>(), 0);
+            }
+        });
+    }
+
+    #[test]
+    fn pinned_root_traced() {
+        use core::{marker::PhantomPinned, pin::Pin};
+
+        use crate::{Arena, Collect, Collector, Rootable};
+
+        struct Task<'b> {
+            state: Gc<'b, u32>,
+            _pinned: PhantomPinned,
+        }
+
+        unsafe impl Collect for Task<'_> {
+            const NEEDS_TRACE: bool = true;
+
+            fn trace(&self, c: &Collector) {
+                self.state.trace(c);
+            }
+        }
+
+        struct TaskRoot;
+
+        impl Rootable for TaskRoot {
+            type Root<'l> = Pin<Gc<'l, Task<'l>>>;
+        }
+
+        let mut a = Arena::<TaskRoot>::new(|mt| {
+            let task = Task {
+                state: Gc::new(7, mt),
+                _pinned: PhantomPinned,
+            };
+            Gc::into_pin(Gc::new(task, mt))
+        });
+        let addr = a.view(|root, _| root.as_ref().get_ref() as *const Task as usize);
+
+        a.complete_collection();
+        assert_eq!(a.allocations(), 2);
+
+        a.view(|root, _| {
+            assert_eq!(root.as_ref().get_ref() as *const Task as usize, addr);
+            assert_eq!(*root.state, 7);
+        });
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        once_arena(|mt| {
+            let a = Gc::new(5u32, mt);
+            let b = Gc::new(5u32, mt);
+
+            let ptr = Gc::into_raw(a);
+            let a2 = unsafe { Gc::<u32>::from_raw(ptr, mt) };
+
+            assert!(Gc::ptr_eq(a, a2));
+            assert!(!Gc::ptr_eq(a, b));
+            assert_eq!(*a2, 5);
+        });
+    }
+
+    #[test]
+    fn hash_consistent_with_eq() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let state = RandomState::new();
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        once_arena(|mt| {
+            for _ in 0..1000 {
+                // A small alphabet and short lengths, so that equal strings are common.
+                let len = (next() % 4) as usize;
+                let a: String = (0..len)
+                    .map(|_| (b'a' + (next() % 3) as u8) as char)
+                    .collect();
+                let b: String = (0..len)
+                    .map(|_| (b'a' + (next() % 3) as u8) as char)
+                    .collect();
+
+                let (a, b) = (Gc::new(a, mt), Gc::new(b, mt));
+                if a == b {
+                    assert_eq!(state.hash_one(a), state.hash_one(b));
+                }
+                assert_eq!(state.hash_one(a), state.hash_one(&*a));
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "pointer was allocated by a different arena"]
+    fn from_raw_wrong_arena() {
+        once_arena(|outer| {
+            let ptr = Gc::into_raw(Gc::new(5u32, outer));
+            once_arena(|mt| {
+                let _ = unsafe { Gc::<u32>::from_raw(ptr, mt) };
+            });
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "pointer was allocated by a different arena"]
+    fn unsize_wrong_arena() {
+        once_arena(|outer| {
+            let array = Gc::new([1u32, 2, 3], outer);
+            once_arena(|inner| {
+                // Safety: Only used to check ownership, never dereferenced.
+                let foreign: Gc<'_, [u32; 3]> = unsafe { core::mem::transmute(array) };
+                let _ = foreign.unsize(inner);
+            });
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "1 pointer returned by `Gc::into_raw` was never passed back"]
+    fn raw_handle_leaked() {
+        once_arena(|mt| {
+            let handed_out = Gc::into_raw(Gc::new(5u32, mt));
+            let _ = unsafe { Gc::<u32>::from_raw(handed_out, mt) };
+
+            // Forgotten by foreign code, and never turned back into a `Gc`.
+            let _leaked = Gc::into_raw(Gc::new(6u32, mt));
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "an object was collected while a pointer returned by `Gc::into_raw`"]
+    fn raw_handle_collected() {
+        use crate::{Arena, Rootable};
+
+        struct Handle;
+
+        impl Rootable for Handle {
+            type Root<'l> = Option<Gc<'l, u32>>;
+        }
+
+        let mut a = Arena::<Handle>::new(|mt| Some(Gc::new(5, mt)));
+        let _handle = a.view(|root, _| Gc::into_raw(root.unwrap()));
+
+        // The object is unrooted while foreign code still holds the handle.
+        a.view_mut(|root, _| *root = None);
+        a.complete_collection();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn owns() {
+        once_arena(|outer| {
+            let gc = Gc::new(5u32, outer);
+            assert!(outer.owns(&gc));
+
+            once_arena(|inner| {
+                // Safety: Only used to check ownership, never dereferenced.
+                let foreign: Gc<'_, u32> = unsafe { core::mem::transmute(gc) };
+                assert!(!inner.owns(&foreign));
+            });
+        });
+    }
+
+    #[test]
+    fn map_key_lookup() {
+        use std::collections::HashMap;
+
+        once_arena(|mt| {
+            let mut strings = HashMap::new();
+            strings.insert(Gc::new(String::from("one"), mt), 1);
+            strings.insert(Gc::new(String::from("two"), mt), 2);
+            assert_eq!(strings.get(&String::from("two")), Some(&2));
+
+            let mut strs = HashMap::new();
+            strs.insert(Gc::from_str("one", mt), 1);
+            strs.insert(Gc::from_str("two", mt), 2);
+            assert_eq!(strs.get("one"), Some(&1));
+            assert_eq!(strs.get("three"), None);
+        });
+    }
+
+    #[test]
+    fn slice_len() {
+        once_arena(|mt| {
+            let values = UniqueGc::<[u32]>::new_zeroed_slice(5, mt);
+            let values = UniqueGc::into_gc(unsafe { values.assume_init() });
+
+            assert_eq!(values.len(), (*values).len());
+            assert_eq!(values.len(), 5);
+            assert!(!values.is_empty());
+        });
+    }
+
+    #[test]
+    fn unsize_array() {
+        once_arena(|mt| {
+            let array = UniqueGc::into_gc(UniqueGc::new_unsizable([1u32, 2, 3], mt));
+            let slice = array.unsize(mt);
+
+            assert_eq!(slice.len(), 3);
+            assert!(slice.iter().copied().eq([1, 2, 3]));
+            assert_eq!(slice.as_ptr().cast::<u32>(), array.as_ptr().cast::<u32>());
+            assert_eq!(*array, [1, 2, 3]);
+
+            // Without room for the length, the elements are copied.
+            let array = Gc::new([4u32, 5], mt);
+            let slice = array.unsize(mt);
+            assert_eq!(*slice, [4, 5]);
+            assert_ne!(slice.as_ptr().cast::<u32>(), array.as_ptr().cast::<u32>());
+        });
+    }
+
+    #[test]
+    fn display_forwards_to_value() {
+        once_arena(|mt| {
+            let s = Gc::from_str("Hello, World!", mt);
+            let n = Gc::new(42u32, mt);
+            assert_eq!(format!("{s} {n:>4}"), "Hello, World!   42");
+
+            let unique = UniqueGc::new(7u32, mt);
+            assert_eq!(unique.to_string(), "7");
+        });
+    }
+
+    #[test]
+    fn eq_same_allocation_skips_compare() {
+        use core::cell::Cell;
+
+        struct Large {
+            words: [u64; 64],
+            compares: Cell<usize>,
+        }
+
+        impl PartialEq for Large {
+            fn eq(&self, other: &Self) -> bool {
+                self.compares.set(self.compares.get() + 1);
+                self.words == other.words
+            }
+        }
+
+        unsafe impl crate::Collect for Large {
+            const NEEDS_TRACE: bool = false;
+
+            fn trace(&self, _c: &crate::Collector) {}
+        }
+
+        once_arena(|mt| {
+            let large = || Large {
+                words: [7; 64],
+                compares: Cell::new(0),
+            };
+            let a = Gc::new(large(), mt);
+            let b = Gc::new(large(), mt);
+
+            let copy = a;
+            assert!(a == copy);
+            assert_eq!(a.compares.get(), 0);
+
+            assert!(a == b);
+            assert_eq!(a.compares.get(), 1);
+        });
+    }
+
+    #[test]
+    fn downcast_mixed_values() {
+        use core::any::Any;
+
+        once_arena(|mt| {
+            let n = Gc::new(42i32, mt);
+            let s = Gc::new(String::from("forty-two"), mt);
+            let values: [&dyn Any; 2] = [n.as_any(), s.as_any()];
+
+            assert_eq!(values[0].downcast_ref::<i32>(), Some(&42));
+            assert_eq!(values[0].downcast_ref::<String>(), None);
+            assert_eq!(values[1].downcast_ref::<String>().unwrap(), "forty-two");
+
+            // The reference points into the allocation, rather than to a copy.
+            let string = values[1].downcast_ref::<String>().unwrap();
+            assert!(core::ptr::eq(string, s.as_ptr()));
+        });
+    }
+}
@@ -9,7 +9,7 @@ use std::{
     ptr::NonNull,
 };
 
-use crate::{gc_vtable::GcVTable, Collect, Collector};
+use crate::{gc_vtable::GcVTable, Collect, Collector, DeepClone, Finalize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct Erased;
@@ -38,28 +38,37 @@ impl<T: ?Sized> GcBox<T> {
         GcInner::<T>::layout(data)
     }
 
-    pub unsafe fn new(ptr: *mut u8, metadata: <T as Pointee>::Metadata, layout: Layout) -> GcBox<T>
+    /// # Safety
+    /// `ptr` must be valid for writes of the whole box, and `regrayed` must outlive it.
+    pub unsafe fn new(
+        ptr: *mut u8,
+        metadata: <T as Pointee>::Metadata,
+        layout: Layout,
+        regrayed: NonNull<GrayList>,
+    ) -> GcBox<T>
     where
         T: Collect,
     {
-        let inner: GcInner<(), <T as Pointee>::Metadata> = GcInner {
-            header: GcHeader {
-                vtable: Cell::new(GcVTable::new::<T>()),
-                next_gray: Cell::new(None),
-                colour: Cell::new(Colour::White),
-                is_live: Cell::new(false),
-                layout,
-            },
-            metadata,
-            data: (),
+        let header = GcHeader {
+            vtable: Cell::new(GcVTable::new::<T>()),
+            link: Cell::new(Link { regrayed }),
+            colour: Cell::new(Colour::White),
+            is_live: Cell::new(false),
+            #[cfg(debug_assertions)]
+            raw_handles: Cell::new(0),
+            #[cfg(feature = "debug-alloc-tags")]
+            site: Cell::new(core::panic::Location::caller()),
+            layout,
         };
 
+        unsafe { ptr.cast::<GcHeader>().write(header) };
+        let gc = GcBox(unsafe { NonNull::new_unchecked(ptr.cast()) }, PhantomData);
         unsafe {
-            ptr.cast::<GcInner<(), <T as Pointee>::Metadata>>()
-                .write(inner)
+            gc.metadata_ptr::<<T as Pointee>::Metadata>()
+                .write(metadata)
         };
 
-        GcBox(unsafe { NonNull::new_unchecked(ptr.cast()) }, PhantomData)
+        gc
     }
 
     pub fn into_raw(self) -> NonNull<()> {
@@ -85,12 +94,10 @@ impl<T: ?Sized> GcBox<T> {
         self.set_uninit();
     }
 
-    pub fn next_gc(&self) -> Option<GcBox<Erased>> {
-        self.header().next_gray.get()
-    }
-
-    pub fn set_next(&self, next: Option<GcBox<Erased>>) {
-        self.header().next_gray.set(next);
+    /// Sets the list which a write barrier adds the box to, for once it is no longer on a gray
+    /// list.
+    pub fn set_regray_list(&self, regrayed: NonNull<GrayList>) {
+        self.header().link.set(Link { regrayed });
     }
 
     pub unsafe fn set_init(&self) {
@@ -113,6 +120,21 @@ impl<T: ?Sized> GcBox<T> {
         self.header().colour.set(c)
     }
 
+    /// Marks the box as possibly containing pointers which haven't been traced.
+    ///
+    /// A black box is turned gray and added to the list of re-grayed objects of its arena, and
+    /// will be traced again before the current mark phase ends.
+    pub fn write_barrier(&self) {
+        if self.colour() == Colour::Black {
+            unsafe { self.set_colour(Colour::Gray) };
+
+            // Safety: A black box isn't on a gray list, so it is linked to the list of its arena,
+            // which outlives it.
+            let regrayed = unsafe { self.header().link.get().regrayed.as_ref() };
+            regrayed.push(self.erase());
+        }
+    }
+
     fn header(&self) -> &GcHeader {
         unsafe { self.0.cast::<GcHeader>().as_ref() }
     }
@@ -129,13 +151,93 @@ impl<T: ?Sized> GcBox<T> {
         self.header().vtable.set(GcVTable::new::<U>())
     }
 
+    /// Gives the box the vtable of another, such as the one which its value was moved from.
+    ///
+    /// # Safety
+    /// The value in the box must have the type which the vtable was created for.
+    pub unsafe fn copy_vtable<U: ?Sized>(&self, from: GcBox<U>) {
+        self.header().vtable.set(from.vtable())
+    }
+
+    /// Sets the vtable to one which will run the finalizer of `T` once it becomes unreachable.
+    ///
+    /// # Safety
+    /// `T` must be the type of the value in the box.
+    pub unsafe fn set_finalizer<'b>(&self)
+    where
+        T: Collect + Finalize<'b>,
+    {
+        self.header().vtable.set(GcVTable::new_finalized::<T>());
+    }
+
+    /// Sets the vtable to one which can deep clone the value, even through a pointer to one of
+    /// its unsized forms.
+    ///
+    /// # Safety
+    /// `T` must be the type of the value in the box.
+    pub unsafe fn set_cloneable(&self)
+    where
+        T: Sized + Collect + DeepClone,
+    {
+        self.header().vtable.set(GcVTable::new_cloneable::<T>());
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn raw_handles(&self) -> usize {
+        self.header().raw_handles.get()
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn add_raw_handle(&self) {
+        let handles = &self.header().raw_handles;
+        handles.set(handles.get() + 1);
+    }
+
+    /// A pointer may be turned back into a `Gc` more than once, so this never goes below zero.
+    #[cfg(debug_assertions)]
+    pub fn remove_raw_handle(&self) {
+        let handles = &self.header().raw_handles;
+        handles.set(handles.get().saturating_sub(1));
+    }
+
+    #[cfg(feature = "debug-alloc-tags")]
+    pub fn site(&self) -> &'static core::panic::Location<'static> {
+        self.header().site.get()
+    }
+
+    #[cfg(feature = "debug-alloc-tags")]
+    pub fn set_site(&self, site: &'static core::panic::Location<'static>) {
+        self.header().site.set(site);
+    }
+
+    /// The metadata is the last thing in the allocation, so that it can be found without knowing
+    /// the layout of the value.
+    fn metadata_ptr<M>(&self) -> *mut M {
+        let offset = self.layout().size() - size_of::<M>();
+        unsafe { self.0.as_ptr().cast::<u8>().add(offset).cast() }
+    }
+
     pub fn metadata(&self) -> <T as Pointee>::Metadata {
-        let ptr = self
-            .0
-            .as_ptr()
-            .cast::<GcInner<(), <T as Pointee>::Metadata>>();
+        unsafe { self.metadata_ptr::<<T as Pointee>::Metadata>().read() }
+    }
+
+    /// Stores new metadata for the value, reinterpreting it as a `U`.
+    ///
+    /// The vtable isn't changed, so the value is still traced and dropped as the original type.
+    ///
+    /// # Safety
+    /// The value must be valid as a `U` with the given metadata, such as by `U` being the
+    /// unsized form of `T`, and the box must have room for the metadata, such as by having been
+    /// allocated with [`Context::allocate_unsizable`].
+    ///
+    /// [`Context::allocate_unsizable`]: crate::context::Context::allocate_unsizable
+    pub unsafe fn unsize<U: ?Sized>(self, metadata: <U as Pointee>::Metadata) -> GcBox<U> {
+        unsafe {
+            self.metadata_ptr::<<U as Pointee>::Metadata>()
+                .write(metadata)
+        };
 
-        unsafe { (&raw const (*ptr).metadata).read() }
+        GcBox(self.0, PhantomData)
     }
 
     pub fn inner_ptr(&self) -> *mut GcInner<T> {
@@ -162,6 +264,17 @@ impl<T: ?Sized> GcBox<T> {
         GcBox(self.0, PhantomData)
     }
 
+    /// Whether the box has room for the metadata of an unsized form of its value, as given by
+    /// [`Context::allocate_unsizable`].
+    ///
+    /// [`Context::allocate_unsizable`]: crate::context::Context::allocate_unsizable
+    pub fn is_unsizable(&self) -> bool
+    where
+        T: Sized,
+    {
+        GcInner::<T, usize>::layout(Layout::new::<T>()).is_ok_and(|layout| layout == self.layout())
+    }
+
     pub unsafe fn transmute<U: ?Sized + Collect>(self) -> GcBox<U> {
         let gc = GcBox(self.0, PhantomData);
         gc.header().vtable.set(GcVTable::new::<U>());
@@ -188,26 +301,84 @@ impl<T: Debug> Debug for GcBox<T> {
 
 struct GcHeader {
     vtable: Cell<&'static GcVTable>,
-    next_gray: Cell<Option<GcBox<Erased>>>,
+    link: Cell<Link>,
     colour: Cell<Colour>,
     is_live: Cell<bool>,
+    /// The number of pointers returned by `Gc::into_raw` which haven't been passed back to
+    /// `Gc::from_raw`.
+    #[cfg(debug_assertions)]
+    raw_handles: Cell<usize>,
+    /// The call outside of this crate which allocated the object.
+    #[cfg(feature = "debug-alloc-tags")]
+    site: Cell<&'static core::panic::Location<'static>>,
     /// The layout of the whole `GcInner`
     layout: Layout,
 }
 
+/// While a box is gray, the next box on the gray list it is on. Otherwise, the list of the arena
+/// which a write barrier adds it to, so that the barrier doesn't need the arena.
+#[derive(Clone, Copy)]
+union Link {
+    next: Option<GcBox<Erased>>,
+    regrayed: NonNull<GrayList>,
+}
+
+/// A stack of gray boxes, linked through their headers.
+#[derive(Default)]
+pub(crate) struct GrayList {
+    first: Cell<Option<GcBox<Erased>>>,
+    len: Cell<usize>,
+}
+
+impl GrayList {
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push(&self, ptr: GcBox<Erased>) {
+        ptr.header().link.set(Link {
+            next: self.first.get(),
+        });
+        self.first.set(Some(ptr));
+        self.len.set(self.len() + 1);
+    }
+
+    /// Removes the last box pushed, which is left linked to the rest of the list until it is
+    /// given a regray list.
+    pub fn pop(&self) -> Option<GcBox<Erased>> {
+        let ptr = self.first.get()?;
+        // Safety: Every box on the list is linked to the next one.
+        self.first.set(unsafe { ptr.header().link.get().next });
+        self.len.set(self.len() - 1);
+        Some(ptr)
+    }
+
+    /// Empties the list, without changing the colour of any of its boxes.
+    pub fn clear(&self) {
+        self.first.set(None);
+        self.len.set(0);
+    }
+}
+
 #[repr(C)]
 pub(crate) struct GcInner<T: ?Sized, M = <T as Pointee>::Metadata> {
     header: GcHeader,
-    metadata: M,
+    /// The metadata follows the value, so that the value is at the same offset whether or not the
+    /// allocation has room for it, and a sized value can be given an unsized form in place.
+    metadata: PhantomData<M>,
     data: T,
 }
 
 impl<T: ?Sized, M> GcInner<T, M> {
     pub fn layout(data: Layout) -> Result<Layout, LayoutError> {
         let layout = Layout::new::<GcHeader>();
-        let (layout, _) = layout.extend(Layout::new::<M>())?;
         let (layout, _) = layout.extend(data)?;
-        Ok(layout.pad_to_align())
+        let (layout, _) = layout.pad_to_align().extend(Layout::new::<M>())?;
+        Ok(layout)
     }
 }
 
@@ -228,5 +399,10 @@ mod tests {
     fn gcinner_layout_sanity() {
         let layout = GcInner::<(), ()>::layout(Layout::new::<()>()).unwrap();
         assert_eq!(layout.size(), Layout::new::<GcHeader>().size());
+
+        // Only unsized values have room for metadata, which follows the value.
+        let array = GcInner::<[u8; 3]>::layout(Layout::new::<[u8; 3]>()).unwrap();
+        let slice = GcInner::<[u8]>::layout(Layout::array::<u8>(3).unwrap()).unwrap();
+        assert_eq!(slice.size(), array.size() + size_of::<usize>());
     }
 }
@@ -0,0 +1,171 @@
+use core::fmt::{Debug, Display};
+use std::error::Error;
+
+use crate::{Collect, Collector, Gc, Mutation, UniqueGc};
+
+/// A garbage collected error, which can hold pointers into the arena, such as an exception
+/// object raised by an interpreted program.
+///
+/// The error is stored as a `Gc<dyn Error>`, and is traced as the type it was created from, so
+/// it can be returned through any number of frames with `?`, and stored in the root like any
+/// other pointer.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, Collect, Collector, GcError, Mutation};
+/// # use std::fmt;
+/// #[derive(Debug)]
+/// struct DivideByZero;
+///
+/// impl fmt::Display for DivideByZero {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("divide by zero")
+///     }
+/// }
+///
+/// impl std::error::Error for DivideByZero {}
+///
+/// unsafe impl Collect for DivideByZero {
+///     const NEEDS_TRACE: bool = false;
+///     fn trace(&self, _c: &Collector) {}
+/// }
+///
+/// fn divide<'b>(a: u32, b: u32, mt: &Mutation<'b>) -> Result<u32, GcError<'b>> {
+///     a.checked_div(b).ok_or_else(|| GcError::new(DivideByZero, mt))
+/// }
+///
+/// # once_arena(|mt| {
+/// let err = divide(1, 0, mt).unwrap_err();
+/// assert_eq!(err.to_string(), "divide by zero");
+/// # });
+/// ```
+pub struct GcError<'b>(Gc<'b, dyn Error + 'b>);
+
+impl<'b> GcError<'b> {
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn new<E: Error + Collect + 'b>(error: E, mt: &Mutation<'b>) -> GcError<'b> {
+        let error = UniqueGc::coerce(UniqueGc::new_unsizable(error, mt), mt);
+        GcError(UniqueGc::into_gc(error))
+    }
+
+    pub fn gc(self) -> Gc<'b, dyn Error + 'b> {
+        self.0
+    }
+}
+
+impl<'b> From<Gc<'b, dyn Error + 'b>> for GcError<'b> {
+    fn from(value: Gc<'b, dyn Error + 'b>) -> Self {
+        GcError(value)
+    }
+}
+
+impl Clone for GcError<'_> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Copy for GcError<'_> {}
+
+impl Debug for GcError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Display for GcError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&*self.0, f)
+    }
+}
+
+impl Error for GcError<'_> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+unsafe impl Collect for GcError<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.0.trace(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt;
+    use std::error::Error;
+
+    use crate::{Arena, Collect, Collector, Gc, GcError, Mutation, Rootable};
+
+    #[derive(Debug)]
+    struct Exception<'b> {
+        message: Gc<'b, str>,
+        value: Gc<'b, i64>,
+    }
+
+    impl fmt::Display for Exception<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}: {}", self.message, self.value)
+        }
+    }
+
+    impl Error for Exception<'_> {}
+
+    unsafe impl Collect for Exception<'_> {
+        const NEEDS_TRACE: bool = true;
+
+        fn trace(&self, c: &Collector) {
+            self.message.trace(c);
+            self.value.trace(c);
+        }
+    }
+
+    fn check<'b>(value: i64, mt: &Mutation<'b>) -> Result<i64, GcError<'b>> {
+        if value < 0 {
+            let exception = Exception {
+                message: Gc::from_str("negative value", mt),
+                value: Gc::new(value, mt),
+            };
+            return Err(GcError::new(exception, mt));
+        }
+
+        Ok(value)
+    }
+
+    fn sum<'b>(values: &[i64], depth: usize, mt: &Mutation<'b>) -> Result<i64, GcError<'b>> {
+        if depth == 0 {
+            return values.iter().map(|v| check(*v, mt)).sum();
+        }
+
+        // Each frame counts itself, so the error has to be propagated through all of them.
+        let _garbage = Gc::new(depth, mt);
+        Ok(sum(values, depth - 1, mt)? + 1)
+    }
+
+    #[test]
+    fn propagated_error_survives_collection() {
+        struct Raised;
+
+        impl Rootable for Raised {
+            type Root<'l> = Option<GcError<'l>>;
+        }
+
+        let mut a = Arena::<Raised>::new(|mt| {
+            assert_eq!(sum(&[1, 2, 3], 5, mt).unwrap(), 11);
+            sum(&[1, -2, 3], 5, mt).err()
+        });
+
+        // The exception, and its message and value.
+        a.complete_collection();
+        assert_eq!(a.allocations(), 3);
+
+        a.view(|raised, _| {
+            let err = raised.unwrap();
+            assert_eq!(err.to_string(), "negative value: -2");
+            assert!(err.source().is_none());
+        });
+    }
+}
@@ -0,0 +1,109 @@
+use core::ops::{Deref, Range};
+use std::fmt::Debug;
+
+use crate::{Collect, Collector, Gc};
+
+/// A view into a range of a garbage collected slice.
+///
+/// Sub-slicing a `GcSlice` doesn't copy any elements, and every view keeps the whole of the
+/// backing slice alive, much like `Bytes` does for byte buffers.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, GcSlice, UniqueGc};
+/// # once_arena(|mt| {
+/// let bytes = UniqueGc::into_gc(UniqueGc::from_slice(b"hello world", mt));
+///
+/// let world = GcSlice::new(bytes).slice(6..11);
+/// assert_eq!(&*world, b"world");
+/// assert_eq!(&*world.slice(1..3), b"or");
+/// # });
+/// ```
+pub struct GcSlice<'b, T> {
+    gc: Gc<'b, [T]>,
+    start: usize,
+    end: usize,
+}
+
+impl<'b, T> GcSlice<'b, T> {
+    /// Constructs a view of the whole of `gc`.
+    pub fn new(gc: Gc<'b, [T]>) -> GcSlice<'b, T> {
+        GcSlice {
+            gc,
+            start: 0,
+            end: gc.len(),
+        }
+    }
+
+    /// Returns a view of `range` within this view.
+    ///
+    /// # Panics
+    /// Panics if the start of the range is greater than its end, or if its end is greater than
+    /// the length of this view.
+    pub fn slice(self, range: Range<usize>) -> GcSlice<'b, T> {
+        assert!(
+            range.start <= range.end,
+            "slice index starts at {} but ends at {}",
+            range.start,
+            range.end
+        );
+        assert!(
+            range.end <= self.len(),
+            "range end index {} out of range for slice of length {}",
+            range.end,
+            self.len()
+        );
+
+        GcSlice {
+            gc: self.gc,
+            start: self.start + range.start,
+            end: self.start + range.end,
+        }
+    }
+
+    /// Returns the backing slice, of which this is a view.
+    pub fn backing(&self) -> Gc<'b, [T]> {
+        self.gc
+    }
+
+    /// Returns the range of the backing slice which this is a view of.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl<'b, T> From<Gc<'b, [T]>> for GcSlice<'b, T> {
+    fn from(gc: Gc<'b, [T]>) -> Self {
+        GcSlice::new(gc)
+    }
+}
+
+impl<T> Deref for GcSlice<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.gc[self.start..self.end]
+    }
+}
+
+unsafe impl<T> Collect for GcSlice<'_, T> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.gc.trace(c);
+    }
+}
+
+impl<T> Clone for GcSlice<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GcSlice<'_, T> {}
+
+impl<T: Debug> Debug for GcSlice<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
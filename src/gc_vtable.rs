@@ -1,12 +1,24 @@
+use core::{
+    mem::{align_of_val_raw, size_of_val_raw},
+    ptr::Pointee,
+};
+
 use crate::{
     gc_box::{Erased, GcBox},
-    Collect, Collector,
+    Collect, Collector, DeepClone, DeepCloner, Finalize, Gc, Mutation,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+type DeepCloneFn = unsafe fn(GcBox<Erased>, &DeepCloner<'_>) -> GcBox<Erased>;
+
+#[derive(Debug, Clone, Copy)]
 pub struct GcVTable {
     collect: unsafe fn(GcBox<Erased>, &Collector),
     drop_in_place: unsafe fn(GcBox<Erased>),
+    finalize: Option<unsafe fn(GcBox<Erased>, &Mutation<'_>)>,
+    deep_clone: Option<DeepCloneFn>,
+    type_name: fn() -> &'static str,
+    size: usize,
+    align: usize,
 }
 
 impl GcVTable {
@@ -17,6 +29,68 @@ impl GcVTable {
     pub unsafe fn drop_in_place(&self, ptr: GcBox<Erased>) {
         unsafe { (self.drop_in_place)(ptr) }
     }
+
+    /// Runs the finalizer of the value, if it has one.
+    pub unsafe fn finalize(&self, ptr: GcBox<Erased>, mt: &Mutation<'_>) {
+        if let Some(finalize) = self.finalize {
+            unsafe { finalize(ptr, mt) }
+        }
+    }
+
+    /// Copies the value into the arena of the cloner, returning the copy.
+    ///
+    /// # Panics
+    /// If the vtable was created without a [`DeepClone`] implementation.
+    pub unsafe fn deep_clone(&self, ptr: GcBox<Erased>, cx: &DeepCloner<'_>) -> GcBox<Erased> {
+        match self.deep_clone {
+            Some(deep_clone) => unsafe { deep_clone(ptr, cx) },
+            None => panic!(
+                "`{}` was not allocated with `UniqueGc::new_cloneable`, so it can't be deep \
+                 cloned through a pointer to an unsized type",
+                self.type_name()
+            ),
+        }
+    }
+
+    pub fn can_deep_clone(&self) -> bool {
+        self.deep_clone.is_some()
+    }
+
+    /// The name of the type which this vtable was created for, as given by
+    /// [`core::any::type_name`].
+    pub fn type_name(&self) -> &'static str {
+        (self.type_name)()
+    }
+
+    /// The size of the type which this vtable was created for, or `0` if it is dynamically
+    /// sized.
+    ///
+    /// Unlike the layout stored in the header of each allocation, this doesn't include the
+    /// header itself, and so doesn't depend on the length of a slice or the type of a trait
+    /// object.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The alignment of the type which this vtable was created for, or `1` if it is dynamically
+    /// sized.
+    pub fn align(&self) -> usize {
+        self.align
+    }
+}
+
+/// Returns the size and alignment of `T` if it is sized, and otherwise zero and one.
+const fn static_layout<T: ?Sized>() -> (usize, usize) {
+    // Only sized types have no metadata.
+    if size_of::<<T as Pointee>::Metadata>() == 0 {
+        // Safety: The metadata is zero-sized, so every bit pattern is valid.
+        let meta = unsafe { core::mem::zeroed() };
+        let ptr: *const T = core::ptr::from_raw_parts(core::ptr::null::<()>(), meta);
+        // Safety: The type is sized, so the layout doesn't depend on the pointer.
+        unsafe { (size_of_val_raw(ptr), align_of_val_raw(ptr)) }
+    } else {
+        (0, 1)
+    }
 }
 
 impl GcVTable {
@@ -33,7 +107,65 @@ impl GcVTable {
                     let gc: GcBox<T> = unsafe { erased.restore_type() };
                     unsafe { std::ptr::drop_in_place(gc.data_ptr()) };
                 },
+                finalize: None,
+                deep_clone: None,
+                type_name: core::any::type_name::<T>,
+                size: static_layout::<T>().0,
+                align: static_layout::<T>().1,
             }
         }
     }
+
+    pub const fn new_finalized<'b, T: Collect + Finalize<'b> + ?Sized>() -> &'static GcVTable {
+        &const {
+            GcVTable {
+                finalize: Some(|erased: GcBox<Erased>, mt: &Mutation<'_>| {
+                    let gc: GcBox<T> = unsafe { erased.restore_type() };
+                    // Safety: The mutation handle belongs to the arena which allocated the value,
+                    // and all of the pointers it contains are valid for as long as it is.
+                    let mt = unsafe { core::mem::transmute::<&Mutation<'_>, &Mutation<'b>>(mt) };
+                    unsafe { gc.data() }.finalize(mt);
+                }),
+                ..*GcVTable::new::<T>()
+            }
+        }
+    }
+
+    pub const fn new_cloneable<T: Collect + DeepClone>() -> &'static GcVTable {
+        &const {
+            GcVTable {
+                deep_clone: Some(|erased: GcBox<Erased>, cx: &DeepCloner<'_>| {
+                    // Safety: The box contains a `T`, which is initialized, as it is reachable.
+                    let gc: Gc<'_, T> = unsafe { Gc::from_box(erased.restore_type()) };
+                    gc.deep_clone(cx).into_box().erase()
+                }),
+                ..*GcVTable::new::<T>()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GcVTable;
+    use crate::{once_arena, Gc};
+
+    #[test]
+    fn static_layout() {
+        let vtable = GcVTable::new::<(u64, u8)>();
+        assert_eq!(vtable.size(), 16);
+        assert_eq!(vtable.align(), 8);
+
+        let unit = GcVTable::new::<()>();
+        assert_eq!((unit.size(), unit.align()), (0, 1));
+
+        let slice = GcVTable::new::<[u32]>();
+        assert_eq!((slice.size(), slice.align()), (0, 1));
+
+        once_arena(|mt| {
+            let vtable = Gc::new([0u16; 5], mt).into_box().vtable();
+            assert_eq!(vtable.size(), 10);
+            assert_eq!(vtable.align(), 2);
+        });
+    }
 }
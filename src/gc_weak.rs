@@ -4,6 +4,8 @@ use crate::{gc_box::GcBox, Collect, Gc, Invariant};
 
 pub struct Weak<'b, T: ?Sized>(NonNull<()>, Invariant<'b>, PhantomData<*const T>);
 
+/// Returns a dangling pointer, which doesn't point to any allocation, and for which
+/// [`Weak::upgrade`] always returns `None`.
 impl<'b, T: ?Sized> Default for Weak<'b, T> {
     fn default() -> Self {
         Weak(
@@ -14,7 +16,16 @@ impl<'b, T: ?Sized> Default for Weak<'b, T> {
     }
 }
 
+impl<T: ?Sized> Clone for Weak<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for Weak<'_, T> {}
+
 impl<'b, T: ?Sized> Weak<'b, T> {
+    /// Constructs a dangling pointer, the same as [`Weak::default`].
     pub fn new() -> Weak<'b, T> {
         Weak::default()
     }
@@ -52,8 +63,12 @@ unsafe impl<'b, T: ?Sized> Collect for Weak<'b, T> {
     fn trace(&self, _c: &crate::Collector) {
         use crate::gc_box::Colour;
 
+        // Only objects which haven't been reached yet are weakly reachable. Gray or black
+        // objects have been reached strongly, and must not be swept.
         if let Some(gc) = self.as_box() {
-            unsafe { gc.set_colour(Colour::Weak) };
+            if gc.colour() == Colour::White {
+                unsafe { gc.set_colour(Colour::Weak) };
+            }
         }
     }
 }
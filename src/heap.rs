@@ -0,0 +1,125 @@
+use std::{
+    alloc::{Allocator, Global},
+    marker::PhantomData,
+};
+
+use crate::{
+    context::{Context, Pacing},
+    Collect, Invariant, Mutation,
+};
+use alloc::boxed::Box;
+
+/// A garbage collected heap, without a root.
+///
+/// A `GcHeap` is a lower level alternative to an [`Arena`], for embedders which need to store
+/// garbage collected pointers alongside other state, rather than inside of a single root type.
+/// Every heap is branded with a unique lifetime `'h`, so pointers allocated inside of it can be
+/// freely stored for the duration of [`GcHeap::scope`], but can't be mixed with those of any
+/// other heap.
+///
+/// As the heap doesn't know which pointers are still in use, the root has to be passed to each
+/// collection, and it is up to the caller to ensure that every pointer used afterwards was
+/// reachable from it.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{GcHeap, Gc};
+/// GcHeap::scope(|heap| {
+///     let mut roots = vec![Gc::new(1, heap.mutation())];
+///     let _garbage = Gc::new(2, heap.mutation());
+///
+///     // Safety: `_garbage` isn't used after the collection.
+///     unsafe { heap.complete_collection(&roots) };
+///     assert_eq!(heap.allocations(), 1);
+///
+///     roots.push(Gc::new(3, heap.mutation()));
+///     assert_eq!(*roots[0] + *roots[1], 4);
+/// });
+/// ```
+///
+/// [`Arena`]: crate::Arena
+pub struct GcHeap<'h, A = Global>
+where
+    A: Allocator,
+{
+    context: Box<Context<A>>,
+    _brand: PhantomData<Invariant<'h>>,
+}
+
+impl GcHeap<'_> {
+    /// Constructs a new heap, and calls the closure with it.
+    pub fn scope<F, Ret>(f: F) -> Ret
+    where
+        F: for<'h> FnOnce(&mut GcHeap<'h>) -> Ret,
+    {
+        GcHeap::scope_paced_in(Pacing::default(), Global, f)
+    }
+}
+
+impl<A> GcHeap<'_, A>
+where
+    A: Allocator + 'static,
+{
+    /// Constructs a new heap with the given pacing and allocator, and calls the closure with it.
+    pub fn scope_paced_in<F, Ret>(pacing: Pacing, alloc: A, f: F) -> Ret
+    where
+        F: for<'h> FnOnce(&mut GcHeap<'h, A>) -> Ret,
+    {
+        let mut heap = GcHeap {
+            context: Box::new(Context::with_capacity_in(pacing, alloc, 0)),
+            _brand: PhantomData,
+        };
+
+        f(&mut heap)
+    }
+}
+
+impl<'h, A> GcHeap<'h, A>
+where
+    A: Allocator,
+{
+    /// Returns a handle through which objects can be allocated in this heap.
+    pub fn mutation(&self) -> &Mutation<'h> {
+        Mutation::new(&self.context)
+    }
+
+    /// Advances the current collection cycle, as with [`Arena::run_collection`].
+    ///
+    /// # Safety
+    /// Every garbage collected pointer into this heap which is used after this call must be
+    /// reachable from `root`.
+    ///
+    /// [`Arena::run_collection`]: crate::Arena::run_collection
+    pub unsafe fn run_collection(&mut self, root: &impl Collect) {
+        // The root is external, so it could have changed since the last call.
+        self.context.set_root_untraced();
+        self.context.advance_collection(root);
+    }
+
+    /// Runs a full collection cycle, as with [`Arena::complete_collection`].
+    ///
+    /// # Safety
+    /// Every garbage collected pointer into this heap which is used after this call must be
+    /// reachable from `root`.
+    ///
+    /// [`Arena::complete_collection`]: crate::Arena::complete_collection
+    pub unsafe fn complete_collection(&mut self, root: &impl Collect) {
+        self.context.set_root_untraced();
+        self.context.run_full_cycle(root);
+    }
+
+    /// Returns the number of objects allocated in this heap.
+    pub fn allocations(&self) -> usize {
+        self.context.allocations()
+    }
+}
+
+impl<A> Drop for GcHeap<'_, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        // As with an `Arena`, every finalizer runs before any value is dropped.
+        self.context.finalize_all();
+    }
+}
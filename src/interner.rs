@@ -0,0 +1,118 @@
+use std::{collections::HashSet, fmt::Debug};
+
+use crate::{Collect, Collector, Gc, Mutation};
+
+/// A table of garbage collected strings, which returns the same [`Gc<str>`] for every string
+/// with the same contents, so that symbols can be compared with [`Gc::ptr_eq`].
+///
+/// Each string is stored once, as the `Gc<str>` itself is the key of the table. Literals and
+/// strings built at runtime are interned alike, and every string is kept alive for as long as
+/// the interner is reachable.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, Gc, StrInterner};
+/// # once_arena(|mt| {
+/// let mut symbols = StrInterner::new();
+///
+/// let a = symbols.intern("lambda", mt);
+/// let b = symbols.intern(&String::from("lambda"), mt);
+/// assert!(Gc::ptr_eq(a, b));
+/// # });
+/// ```
+pub struct StrInterner<'b>(HashSet<Gc<'b, str>>);
+
+impl<'b> StrInterner<'b> {
+    pub fn new() -> StrInterner<'b> {
+        StrInterner(HashSet::new())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the interned copy of the string, allocating it if it hasn't been interned yet.
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn intern(&mut self, s: &str, mt: &Mutation<'b>) -> Gc<'b, str> {
+        if let Some(interned) = self.0.get(s) {
+            return *interned;
+        }
+
+        let interned = Gc::from_str(s, mt);
+        self.0.insert(interned);
+        interned
+    }
+
+    /// Returns the interned copy of the string, without interning it if it is missing.
+    pub fn get(&self, s: &str) -> Option<Gc<'b, str>> {
+        self.0.get(s).copied()
+    }
+
+    /// Returns an iterator over every interned string, in an arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = Gc<'b, str>> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl Default for StrInterner<'_> {
+    fn default() -> Self {
+        StrInterner::new()
+    }
+}
+
+impl Debug for StrInterner<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.iter()).finish()
+    }
+}
+
+unsafe impl Collect for StrInterner<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.0.trace(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{Arena, Gc, Rootable, StrInterner};
+
+    #[test]
+    fn interned_strings_are_shared() {
+        struct Symbols;
+
+        impl Rootable for Symbols {
+            type Root<'l> = StrInterner<'l>;
+        }
+
+        let mut a = Arena::<Symbols>::new(|_| StrInterner::new());
+
+        let (literal, runtime) = a.view_mut(|symbols, mt| {
+            let literal = symbols.intern("define", mt);
+            let runtime = symbols.intern(&["de", "fine"].concat(), mt);
+            assert!(Gc::ptr_eq(literal, runtime));
+
+            symbols.intern("lambda", mt);
+            (literal.as_ptr(), runtime.as_ptr())
+        });
+        assert_eq!(literal, runtime);
+
+        a.complete_collection();
+        assert_eq!(a.allocations(), 2);
+
+        a.view_mut(|symbols, mt| {
+            let name: Cow<'static, str> = Cow::Borrowed("define");
+            assert_eq!(symbols.intern(&name, mt).as_ptr(), literal);
+            assert_eq!(symbols.get("lambda").as_deref(), Some("lambda"));
+            assert_eq!(symbols.get("let"), None);
+            assert_eq!(symbols.len(), 2);
+        });
+    }
+}
@@ -1,14 +1,27 @@
-#![feature(ptr_metadata, strict_provenance, allocator_api)]
+#![feature(ptr_metadata, allocator_api, layout_for_ptr, unsize)]
 #![deny(unsafe_op_in_unsafe_fn)]
 #![doc = include_str!("../README.md")]
 
 extern crate alloc;
 
 mod arena;
+mod by_address;
 mod collect;
 mod context;
+mod deep_clone;
+mod external;
+mod finalize;
+mod frozen_gc;
 mod gc;
+mod gc_error;
+mod gc_slice;
 mod gc_vtable;
+mod heap;
+mod interner;
+mod partial_slice;
+mod retain;
+mod rooted;
+mod tagged_gc;
 mod unique_gc;
 mod write;
 
@@ -17,15 +30,31 @@ mod gc_weak;
 mod invariant;
 pub mod locked;
 
-pub use arena::{Arena, Rootable};
-pub use collect::Collect;
-pub use context::{Collector, Mutation};
+pub use arena::{
+    Arena, Checkpoint, CollectionCursor, CollectionReport, CursorPhase, GenerationStats, Rootable,
+    TypeStats,
+};
+pub use by_address::{ByAddress, GcIdentityMap};
+pub use collect::{Collect, TransparentCollect};
+pub use context::{Collector, Mutation, Pacing};
+pub use deep_clone::{DeepClone, DeepCloner};
+pub use external::External;
+pub use finalize::Finalize;
+pub use frozen_gc::FrozenGc;
 pub use gc::Gc;
+pub use gc_error::GcError;
+pub use gc_slice::GcSlice;
 pub use gc_weak::Weak;
+pub use heap::GcHeap;
+pub use interner::StrInterner;
+pub use partial_slice::PartialSlice;
+pub use retain::Retain;
+pub use rooted::{PinGuard, Rooted, ThreadLocalRoots};
+pub use tagged_gc::TaggedGc;
 pub use unique_gc::UniqueGc;
 pub use write::Write;
 
-use invariant::Invariant;
+pub use invariant::Invariant;
 
 pub fn once_arena<F, R>(f: F) -> R
 where
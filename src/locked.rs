@@ -2,9 +2,15 @@
 //!
 //! [`Gc`]: crate::Gc
 
-use std::cell::{Cell, OnceCell, RefCell};
+use std::{
+    borrow::Borrow,
+    cell::{Cell, OnceCell, RefCell},
+    collections::{BTreeMap, BinaryHeap, HashMap, VecDeque},
+    fmt::Debug,
+    hash::{BuildHasher, Hash, RandomState},
+};
 
-use crate::Collect;
+use crate::{Collect, Gc, Weak, Write};
 
 /// A marker for types which allow a [`Collect`] implementation on an
 /// interiorly mutable type.
@@ -46,6 +52,74 @@ impl<T: ?Sized> LockedCell<T> {
     }
 }
 
+impl<T: Copy> Write<LockedCell<T>> {
+    /// Updates the contained value using a function.
+    ///
+    /// The owning [`Gc`] has already been marked as modified by acquiring the [`Write`], so this
+    /// is equivalent to calling `get`, then `set` on the unlocked cell.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{locked::LockedCell, once_arena, Gc};
+    /// # once_arena(|mt| {
+    /// let counter = Gc::new(LockedCell::new(5u32), mt);
+    ///
+    /// counter.write().update(|x| x + 1);
+    ///
+    /// assert_eq!(counter.get(), 6);
+    /// # });
+    /// ```
+    ///
+    /// [`Gc`]: crate::Gc
+    pub fn update(&self, f: impl FnOnce(T) -> T) {
+        let cell = self.unlock();
+        cell.set(f(cell.get()));
+    }
+}
+
+/// Swaps the values of two cells, which may be owned by different objects.
+///
+/// Both owning [`Gc`]s have already been marked as modified by acquiring the [`Write`]s, so
+/// values moved between them can't be missed by an incremental collection in progress, unlike
+/// when calling [`mem::swap`] on the contents of the cells.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{locked::{self, LockedCell}, once_arena, Gc};
+/// # once_arena(|mt| {
+/// let a = Gc::new(LockedCell::new(1u32), mt);
+/// let b = Gc::new(LockedCell::new(2u32), mt);
+///
+/// locked::swap(a.write(), b.write());
+///
+/// assert_eq!((a.get(), b.get()), (2, 1));
+/// # });
+/// ```
+///
+/// [`mem::swap`]: std::mem::swap
+pub fn swap<T>(a: &Write<LockedCell<T>>, b: &Write<LockedCell<T>>) {
+    a.unlock().swap(b.unlock());
+}
+
+/// Replaces the value of a cell, returning the old value.
+///
+/// The owning [`Gc`] has already been marked as modified by acquiring the [`Write`], so this is
+/// equivalent to calling `replace` on the unlocked cell.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{locked::{self, LockedCell}, once_arena, Gc};
+/// # once_arena(|mt| {
+/// let a = Gc::new(LockedCell::new(1u32), mt);
+///
+/// assert_eq!(locked::replace(a.write(), 2), 1);
+/// assert_eq!(a.get(), 2);
+/// # });
+/// ```
+pub fn replace<T>(dest: &Write<LockedCell<T>>, value: T) -> T {
+    dest.unlock().replace(value)
+}
+
 impl<T> Clone for LockedCell<T>
 where
     T: Copy,
@@ -115,6 +189,92 @@ unsafe impl<T: Copy + Collect> Collect for LockedCell<T> {
     }
 }
 
+/// An optional weak pointer, which can be replaced when inside a [`Gc`].
+///
+/// This is a [`LockedCell<Option<Weak<'b, T>>>`](LockedCell), for slots such as back-edges to a
+/// parent, which shouldn't keep what they point to alive. Setting it still requires the owning
+/// [`Gc`] to be marked as modified, as a weak pointer keeps the storage of its target allocated
+/// until it is no longer reachable.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, locked::WeakCell, Gc};
+/// # once_arena(|mt| {
+/// let parent = Gc::new(5, mt);
+/// let child = Gc::new(WeakCell::new(), mt);
+/// assert_eq!(child.upgrade(), None);
+///
+/// child.write().set(Some(parent));
+/// assert_eq!(child.upgrade().as_deref(), Some(&5));
+/// # });
+/// ```
+///
+/// [`Gc`]: crate::Gc
+#[repr(transparent)]
+pub struct WeakCell<'b, T: ?Sized>(LockedCell<Option<Weak<'b, T>>>);
+
+impl<'b, T: ?Sized> WeakCell<'b, T> {
+    /// Constructs an empty cell.
+    pub const fn new() -> WeakCell<'b, T> {
+        WeakCell(LockedCell::new(None))
+    }
+
+    /// Constructs a cell pointing weakly to `value`.
+    pub fn from_gc(value: Gc<'b, T>) -> WeakCell<'b, T> {
+        WeakCell(LockedCell::new(Some(Gc::downgrade(value))))
+    }
+
+    pub fn get(&self) -> Option<Weak<'b, T>> {
+        self.0.get()
+    }
+
+    /// Returns the pointed to value, if the cell isn't empty and the value is still alive.
+    pub fn upgrade(&self) -> Option<Gc<'b, T>> {
+        self.get().and_then(Weak::upgrade)
+    }
+}
+
+impl<'b, T: ?Sized> Write<WeakCell<'b, T>> {
+    /// Points the cell weakly to `value`, or empties it.
+    pub fn set(&self, value: Option<Gc<'b, T>>) {
+        self.project(|cell| &cell.0)
+            .unlock()
+            .set(value.map(Gc::downgrade));
+    }
+
+    /// Empties the cell, returning the previous value if it was still alive.
+    pub fn take(&self) -> Option<Gc<'b, T>> {
+        let cell = self.project(|cell| &cell.0).unlock();
+        cell.take().and_then(Weak::upgrade)
+    }
+}
+
+impl<T: ?Sized> Default for WeakCell<'_, T> {
+    fn default() -> Self {
+        WeakCell::new()
+    }
+}
+
+impl<T: ?Sized> Clone for WeakCell<'_, T> {
+    fn clone(&self) -> Self {
+        WeakCell(self.0.clone())
+    }
+}
+
+impl<T: ?Sized + Debug> Debug for WeakCell<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WeakCell").field(&self.upgrade()).finish()
+    }
+}
+
+unsafe impl<T: ?Sized> Collect for WeakCell<'_, T> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &crate::Collector) {
+        self.0.trace(c);
+    }
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
 pub struct LockedRefCell<T: ?Sized>(core::cell::RefCell<T>);
@@ -166,6 +326,731 @@ unsafe impl<T: ?Sized + Collect> Collect for LockedRefCell<T> {
     }
 }
 
+/// An owned, boxed value which can be replaced when inside a [`Gc`].
+///
+/// [`Gc`]: crate::Gc
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct LockedBox<T: ?Sized>(core::cell::RefCell<Box<T>>);
+
+impl<T> LockedBox<T> {
+    pub fn new(value: T) -> LockedBox<T> {
+        LockedBox(RefCell::new(Box::new(value)))
+    }
+}
+
+impl<T: ?Sized> LockedBox<T> {
+    pub fn from_box(value: Box<T>) -> LockedBox<T> {
+        LockedBox(RefCell::new(value))
+    }
+
+    pub fn into_inner(self) -> Box<T> {
+        self.0.into_inner()
+    }
+
+    pub fn borrow(&self) -> core::cell::Ref<'_, T> {
+        core::cell::Ref::map(self.0.borrow(), |b| &**b)
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut()
+    }
+}
+
+impl<T: ?Sized> Write<LockedBox<T>> {
+    /// Replaces the boxed value, returning the old one.
+    ///
+    /// # Panics
+    /// If the value is currently borrowed.
+    pub fn replace(&self, new: Box<T>) -> Box<T> {
+        self.unlock().replace(new)
+    }
+}
+
+impl<T: ?Sized> Unlock for LockedBox<T> {
+    type Unlocked = core::cell::RefCell<Box<T>>;
+
+    unsafe fn unlock_unchecked(&self) -> &Self::Unlocked {
+        &self.0
+    }
+}
+
+unsafe impl<T: ?Sized + Collect> Collect for LockedBox<T> {
+    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+
+    fn trace(&self, c: &crate::Collector) {
+        self.borrow().trace(c);
+    }
+}
+
+/// A hash map of garbage collected values, which can be modified when inside a [`Gc`].
+///
+/// This is a [`LockedRefCell<HashMap<K, Gc<'b, V>>>`](LockedRefCell), with methods which
+/// borrow the map only for as long as each operation takes.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, locked::GcMap, Gc};
+/// # once_arena(|mt| {
+/// let env = Gc::new(GcMap::new(), mt);
+/// env.write().insert(1, Gc::new(5, mt));
+///
+/// assert_eq!(env.get(&1).as_deref(), Some(&5));
+/// assert!(env.write().remove(&1).is_some());
+/// assert!(env.is_empty());
+/// # });
+/// ```
+///
+/// [`Gc`]: crate::Gc
+#[repr(transparent)]
+pub struct GcMap<'b, K, V: ?Sized, S = RandomState>(LockedRefCell<HashMap<K, Gc<'b, V>, S>>);
+
+impl<'b, K, V: ?Sized> GcMap<'b, K, V> {
+    pub fn new() -> GcMap<'b, K, V> {
+        GcMap(LockedRefCell::new(HashMap::new()))
+    }
+}
+
+impl<'b, K, V: ?Sized, S> GcMap<'b, K, V, S> {
+    pub fn with_hasher(hasher: S) -> GcMap<'b, K, V, S> {
+        GcMap(LockedRefCell::new(HashMap::with_hasher(hasher)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// Returns a borrow of the underlying map.
+    pub fn borrow(&self) -> core::cell::Ref<'_, HashMap<K, Gc<'b, V>, S>> {
+        self.0.borrow()
+    }
+
+    pub fn get_mut(&mut self) -> &mut HashMap<K, Gc<'b, V>, S> {
+        self.0.get_mut()
+    }
+
+    pub fn into_inner(self) -> HashMap<K, Gc<'b, V>, S> {
+        self.0.into_inner()
+    }
+}
+
+impl<'b, K: Eq + Hash, V: ?Sized, S: BuildHasher> GcMap<'b, K, V, S> {
+    pub fn get<Q>(&self, k: &Q) -> Option<Gc<'b, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.0.borrow().get(k).copied()
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.0.borrow().contains_key(k)
+    }
+}
+
+impl<'b, K: Eq + Hash, V: ?Sized, S: BuildHasher> Write<GcMap<'b, K, V, S>> {
+    /// Inserts a value into the map, returning the previous value for the key.
+    pub fn insert(&self, k: K, v: Gc<'b, V>) -> Option<Gc<'b, V>> {
+        self.project(|map| &map.0)
+            .unlock()
+            .borrow_mut()
+            .insert(k, v)
+    }
+
+    /// Removes a key from the map, returning its value.
+    pub fn remove<Q>(&self, k: &Q) -> Option<Gc<'b, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.project(|map| &map.0).unlock().borrow_mut().remove(k)
+    }
+}
+
+impl<K, V: ?Sized> Default for GcMap<'_, K, V> {
+    fn default() -> Self {
+        GcMap::new()
+    }
+}
+
+impl<K: Debug, V: ?Sized + Debug, S> Debug for GcMap<'_, K, V, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.0.borrow().iter()).finish()
+    }
+}
+
+unsafe impl<K: Collect, V: ?Sized, S> Collect for GcMap<'_, K, V, S> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &crate::Collector) {
+        self.0.trace(c);
+    }
+}
+
+/// A hash map of weakly referenced garbage collected values, which can be modified when inside a
+/// [`Gc`].
+///
+/// The map doesn't keep its values alive. Entries whose values have been collected are removed
+/// when they are looked up, or all at once with [`GcWeakMap::prune`], which makes this suitable
+/// as a cache.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, locked::GcWeakMap, Gc};
+/// # once_arena(|mt| {
+/// let cache = Gc::new(GcWeakMap::new(), mt);
+/// let five = Gc::new(5, mt);
+/// cache.write().insert("five", five);
+///
+/// assert_eq!(cache.get("five").as_deref(), Some(&5));
+/// assert_eq!(cache.get("six"), None);
+/// # });
+/// ```
+///
+/// [`Gc`]: crate::Gc
+#[repr(transparent)]
+pub struct GcWeakMap<'b, K, V: ?Sized, S = RandomState>(LockedRefCell<HashMap<K, Weak<'b, V>, S>>);
+
+impl<'b, K, V: ?Sized> GcWeakMap<'b, K, V> {
+    pub fn new() -> GcWeakMap<'b, K, V> {
+        GcWeakMap(LockedRefCell::new(HashMap::new()))
+    }
+}
+
+impl<'b, K, V: ?Sized, S> GcWeakMap<'b, K, V, S> {
+    pub fn with_hasher(hasher: S) -> GcWeakMap<'b, K, V, S> {
+        GcWeakMap(LockedRefCell::new(HashMap::with_hasher(hasher)))
+    }
+
+    /// Returns the number of entries in the map, including those whose values have been
+    /// collected but which haven't yet been pruned.
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// Returns a borrow of the underlying map.
+    pub fn borrow(&self) -> core::cell::Ref<'_, HashMap<K, Weak<'b, V>, S>> {
+        self.0.borrow()
+    }
+
+    /// Removes every entry whose value has been collected, returning how many were removed.
+    ///
+    /// # Panics
+    /// Panics if the map is currently borrowed.
+    pub fn prune(&self) -> usize {
+        // Safety: Removing pointers from an object never requires a write barrier.
+        let mut map = unsafe { self.0.unlock_unchecked() }.borrow_mut();
+        let len = map.len();
+        map.retain(|_, v| v.upgrade().is_some());
+        len - map.len()
+    }
+
+    pub fn into_inner(self) -> HashMap<K, Weak<'b, V>, S> {
+        self.0.into_inner()
+    }
+}
+
+impl<'b, K: Eq + Hash, V: ?Sized, S: BuildHasher> GcWeakMap<'b, K, V, S> {
+    /// Returns the value for the key, if it is still alive, and otherwise removes its entry.
+    ///
+    /// The entry isn't removed if the map is currently borrowed.
+    pub fn get<Q>(&self, k: &Q) -> Option<Gc<'b, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let weak = *self.0.borrow().get(k)?;
+        let value = weak.upgrade();
+
+        if value.is_none() {
+            // Safety: Removing pointers from an object never requires a write barrier.
+            if let Ok(mut map) = unsafe { self.0.unlock_unchecked() }.try_borrow_mut() {
+                map.remove(k);
+            }
+        }
+
+        value
+    }
+}
+
+impl<'b, K: Eq + Hash, V: ?Sized, S: BuildHasher> Write<GcWeakMap<'b, K, V, S>> {
+    /// Inserts a weak reference to the value into the map, returning the previous value for the
+    /// key, if it is still alive.
+    pub fn insert(&self, k: K, v: Gc<'b, V>) -> Option<Gc<'b, V>> {
+        self.project(|map| &map.0)
+            .unlock()
+            .borrow_mut()
+            .insert(k, Gc::downgrade(v))
+            .and_then(Weak::upgrade)
+    }
+
+    /// Removes a key from the map, returning its value, if it is still alive.
+    pub fn remove<Q>(&self, k: &Q) -> Option<Gc<'b, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.project(|map| &map.0)
+            .unlock()
+            .borrow_mut()
+            .remove(k)
+            .and_then(Weak::upgrade)
+    }
+}
+
+impl<K, V: ?Sized> Default for GcWeakMap<'_, K, V> {
+    fn default() -> Self {
+        GcWeakMap::new()
+    }
+}
+
+impl<K: Debug, V: ?Sized + Debug, S> Debug for GcWeakMap<'_, K, V, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.borrow().iter().map(|(k, v)| (k, v.upgrade())))
+            .finish()
+    }
+}
+
+unsafe impl<K: Collect, V: ?Sized, S> Collect for GcWeakMap<'_, K, V, S> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &crate::Collector) {
+        self.0.trace(c);
+    }
+}
+
+/// A double-ended queue which can be pushed to and popped from when inside a [`Gc`].
+///
+/// This is a [`LockedRefCell<VecDeque<T>>`](LockedRefCell), with methods which borrow the queue
+/// only for as long as each operation takes, which makes it suitable as a garbage collected work
+/// queue.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, locked::LockedVecDeque, Gc};
+/// # once_arena(|mt| {
+/// let queue = Gc::new(LockedVecDeque::new(), mt);
+/// queue.write().push_back(Gc::new(1, mt));
+/// queue.write().push_back(Gc::new(2, mt));
+///
+/// assert_eq!(queue.write().pop_front().as_deref(), Some(&1));
+/// assert_eq!(queue.len(), 1);
+/// # });
+/// ```
+///
+/// [`Gc`]: crate::Gc
+#[derive(Debug, Default, Clone)]
+#[repr(transparent)]
+pub struct LockedVecDeque<T>(RefCell<VecDeque<T>>);
+
+impl<T> LockedVecDeque<T> {
+    pub const fn new() -> LockedVecDeque<T> {
+        LockedVecDeque(RefCell::new(VecDeque::new()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    pub fn borrow(&self) -> core::cell::Ref<'_, VecDeque<T>> {
+        self.0.borrow()
+    }
+
+    pub fn get_mut(&mut self) -> &mut VecDeque<T> {
+        self.0.get_mut()
+    }
+
+    pub fn into_inner(self) -> VecDeque<T> {
+        self.0.into_inner()
+    }
+}
+
+impl<T: Copy> LockedVecDeque<T> {
+    pub fn front(&self) -> Option<T> {
+        self.0.borrow().front().copied()
+    }
+
+    pub fn back(&self) -> Option<T> {
+        self.0.borrow().back().copied()
+    }
+}
+
+impl<T> Write<LockedVecDeque<T>> {
+    /// # Panics
+    /// If the queue is currently borrowed.
+    pub fn push_back(&self, value: T) {
+        self.unlock().borrow_mut().push_back(value);
+    }
+
+    /// # Panics
+    /// If the queue is currently borrowed.
+    pub fn push_front(&self, value: T) {
+        self.unlock().borrow_mut().push_front(value);
+    }
+
+    /// # Panics
+    /// If the queue is currently borrowed.
+    pub fn pop_front(&self) -> Option<T> {
+        self.unlock().borrow_mut().pop_front()
+    }
+
+    /// # Panics
+    /// If the queue is currently borrowed.
+    pub fn pop_back(&self) -> Option<T> {
+        self.unlock().borrow_mut().pop_back()
+    }
+}
+
+impl<T> From<VecDeque<T>> for LockedVecDeque<T> {
+    fn from(value: VecDeque<T>) -> Self {
+        LockedVecDeque(RefCell::new(value))
+    }
+}
+
+impl<T> Unlock for LockedVecDeque<T> {
+    type Unlocked = RefCell<VecDeque<T>>;
+
+    unsafe fn unlock_unchecked(&self) -> &Self::Unlocked {
+        &self.0
+    }
+}
+
+unsafe impl<T: Collect> Collect for LockedVecDeque<T> {
+    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+
+    fn trace(&self, c: &crate::Collector) {
+        self.0.borrow().trace(c);
+    }
+}
+
+/// An ordered map which can be inserted into and removed from when inside a [`Gc`].
+///
+/// This is a [`LockedRefCell<BTreeMap<K, V>>`](LockedRefCell), with methods which borrow the map
+/// only for as long as each operation takes. Range queries can be made through
+/// [`LockedBTreeMap::borrow`].
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, locked::LockedBTreeMap, Gc};
+/// # once_arena(|mt| {
+/// let env = Gc::new(LockedBTreeMap::new(), mt);
+/// env.write().insert("b", Gc::new(2, mt));
+/// env.write().insert("a", Gc::new(1, mt));
+///
+/// assert_eq!(env.get("a").as_deref(), Some(&1));
+/// assert!(env.borrow().keys().copied().eq(["a", "b"]));
+/// # });
+/// ```
+///
+/// [`Gc`]: crate::Gc
+#[derive(Debug, Default, Clone)]
+#[repr(transparent)]
+pub struct LockedBTreeMap<K, V>(RefCell<BTreeMap<K, V>>);
+
+impl<K, V> LockedBTreeMap<K, V> {
+    pub const fn new() -> LockedBTreeMap<K, V> {
+        LockedBTreeMap(RefCell::new(BTreeMap::new()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    pub fn borrow(&self) -> core::cell::Ref<'_, BTreeMap<K, V>> {
+        self.0.borrow()
+    }
+
+    pub fn get_mut(&mut self) -> &mut BTreeMap<K, V> {
+        self.0.get_mut()
+    }
+
+    pub fn into_inner(self) -> BTreeMap<K, V> {
+        self.0.into_inner()
+    }
+}
+
+impl<K: Ord, V> LockedBTreeMap<K, V> {
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.0.borrow().contains_key(k)
+    }
+}
+
+impl<K: Ord, V: Copy> LockedBTreeMap<K, V> {
+    pub fn get<Q>(&self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.0.borrow().get(k).copied()
+    }
+}
+
+impl<K: Ord, V> Write<LockedBTreeMap<K, V>> {
+    /// Inserts a value into the map, returning the previous value for the key.
+    ///
+    /// # Panics
+    /// If the map is currently borrowed.
+    pub fn insert(&self, k: K, v: V) -> Option<V> {
+        self.unlock().borrow_mut().insert(k, v)
+    }
+
+    /// Removes a key from the map, returning its value.
+    ///
+    /// # Panics
+    /// If the map is currently borrowed.
+    pub fn remove<Q>(&self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.unlock().borrow_mut().remove(k)
+    }
+}
+
+impl<K, V> From<BTreeMap<K, V>> for LockedBTreeMap<K, V> {
+    fn from(value: BTreeMap<K, V>) -> Self {
+        LockedBTreeMap(RefCell::new(value))
+    }
+}
+
+impl<K, V> Unlock for LockedBTreeMap<K, V> {
+    type Unlocked = RefCell<BTreeMap<K, V>>;
+
+    unsafe fn unlock_unchecked(&self) -> &Self::Unlocked {
+        &self.0
+    }
+}
+
+unsafe impl<K: Collect, V: Collect> Collect for LockedBTreeMap<K, V> {
+    const NEEDS_TRACE: bool = K::NEEDS_TRACE || V::NEEDS_TRACE;
+
+    fn trace(&self, c: &crate::Collector) {
+        self.0.borrow().trace(c);
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A standard collection which can be wrapped in a [`Locked`], to be modified when inside a
+/// [`Gc`].
+///
+/// This trait is sealed, and is implemented for [`Vec`], [`VecDeque`], [`BinaryHeap`] and
+/// [`BTreeMap`].
+///
+/// [`Gc`]: crate::Gc
+pub trait GcCollection: Collect + sealed::Sealed {
+    type Item;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds an item to the collection, at the back for sequences.
+    fn insert_item(&mut self, item: Self::Item);
+
+    fn clear(&mut self);
+}
+
+impl<T> sealed::Sealed for Vec<T> {}
+
+impl<T: Collect> GcCollection for Vec<T> {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn insert_item(&mut self, item: T) {
+        self.push(item);
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+}
+
+impl<T> sealed::Sealed for VecDeque<T> {}
+
+impl<T: Collect> GcCollection for VecDeque<T> {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+
+    fn insert_item(&mut self, item: T) {
+        self.push_back(item);
+    }
+
+    fn clear(&mut self) {
+        VecDeque::clear(self);
+    }
+}
+
+impl<T> sealed::Sealed for BinaryHeap<T> {}
+
+impl<T: Collect + Ord> GcCollection for BinaryHeap<T> {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        BinaryHeap::len(self)
+    }
+
+    fn insert_item(&mut self, item: T) {
+        self.push(item);
+    }
+
+    fn clear(&mut self) {
+        BinaryHeap::clear(self);
+    }
+}
+
+impl<K, V> sealed::Sealed for BTreeMap<K, V> {}
+
+impl<K: Collect + Ord, V: Collect> GcCollection for BTreeMap<K, V> {
+    type Item = (K, V);
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn insert_item(&mut self, (k, v): (K, V)) {
+        self.insert(k, v);
+    }
+
+    fn clear(&mut self) {
+        BTreeMap::clear(self);
+    }
+}
+
+/// A standard collection which can be modified when inside a [`Gc`].
+///
+/// This is a [`LockedRefCell`] restricted to the collections implementing [`GcCollection`],
+/// with methods which borrow the collection only for as long as each operation takes. Any other
+/// modification can be made through [`Write::update`], which fires the write barrier once for
+/// the whole closure.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, locked::Locked, Gc};
+/// # use std::collections::BinaryHeap;
+/// # once_arena(|mt| {
+/// let heap = Gc::new(Locked::<BinaryHeap<Gc<u32>>>::new(), mt);
+/// heap.write().insert(Gc::new(1, mt));
+/// heap.write().insert(Gc::new(3, mt));
+///
+/// assert_eq!(heap.write().update(|heap| heap.pop()).as_deref(), Some(&3));
+/// assert_eq!(heap.len(), 1);
+/// # });
+/// ```
+///
+/// [`Gc`]: crate::Gc
+#[derive(Debug, Default, Clone)]
+#[repr(transparent)]
+pub struct Locked<C: GcCollection>(RefCell<C>);
+
+impl<C: GcCollection> Locked<C> {
+    pub fn new() -> Locked<C>
+    where
+        C: Default,
+    {
+        Locked(RefCell::default())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn borrow(&self) -> core::cell::Ref<'_, C> {
+        self.0.borrow()
+    }
+
+    pub fn get_mut(&mut self) -> &mut C {
+        self.0.get_mut()
+    }
+
+    pub fn into_inner(self) -> C {
+        self.0.into_inner()
+    }
+}
+
+impl<C: GcCollection> Write<Locked<C>> {
+    /// # Panics
+    /// If the collection is currently borrowed.
+    pub fn insert(&self, item: C::Item) {
+        self.unlock().borrow_mut().insert_item(item);
+    }
+
+    /// # Panics
+    /// If the collection is currently borrowed.
+    pub fn clear(&self) {
+        self.unlock().borrow_mut().clear();
+    }
+
+    /// Calls the closure with mutable access to the collection.
+    ///
+    /// # Panics
+    /// If the collection is currently borrowed, including from within the closure.
+    pub fn update<R>(&self, f: impl FnOnce(&mut C) -> R) -> R {
+        f(&mut self.unlock().borrow_mut())
+    }
+}
+
+impl<C: GcCollection> From<C> for Locked<C> {
+    fn from(value: C) -> Self {
+        Locked(RefCell::new(value))
+    }
+}
+
+impl<C: GcCollection> Unlock for Locked<C> {
+    type Unlocked = RefCell<C>;
+
+    unsafe fn unlock_unchecked(&self) -> &Self::Unlocked {
+        &self.0
+    }
+}
+
+unsafe impl<C: GcCollection> Collect for Locked<C> {
+    const NEEDS_TRACE: bool = C::NEEDS_TRACE;
+
+    fn trace(&self, c: &crate::Collector) {
+        self.0.borrow().trace(c);
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct LockedOnceCell<T>(core::cell::OnceCell<T>);
@@ -207,3 +1092,388 @@ unsafe impl<T: Collect> Collect for LockedOnceCell<T> {
         self.get().trace(c);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        locked::{
+            self, GcMap, GcWeakMap, Locked, LockedBTreeMap, LockedBox, LockedCell, LockedVecDeque,
+        },
+        once_arena, Arena, Collect, Collector, Gc, Pacing, Rootable, Write,
+    };
+
+    #[test]
+    fn locked_cell_update() {
+        once_arena(|mt| {
+            let counter = Gc::new(LockedCell::new(0u32), mt);
+
+            for _ in 0..10 {
+                counter.write().update(|x| x + 1);
+            }
+
+            assert_eq!(counter.get(), 10);
+        });
+    }
+
+    #[test]
+    fn locked_box_replace_during_collection() {
+        struct Tree<'b> {
+            leaf: Gc<'b, u32>,
+        }
+
+        unsafe impl Collect for Tree<'_> {
+            const NEEDS_TRACE: bool = true;
+
+            fn trace(&self, c: &Collector) {
+                self.leaf.trace(c);
+            }
+        }
+
+        struct Node<'b> {
+            child: LockedBox<Tree<'b>>,
+        }
+
+        unsafe impl Collect for Node<'_> {
+            const NEEDS_TRACE: bool = true;
+
+            fn trace(&self, c: &Collector) {
+                self.child.trace(c);
+            }
+        }
+
+        struct Root<'b>(Gc<'b, Node<'b>>);
+
+        unsafe impl Collect for Root<'_> {
+            const NEEDS_TRACE: bool = true;
+
+            fn trace(&self, c: &Collector) {
+                self.0.trace(c);
+            }
+        }
+
+        impl Rootable for Root<'static> {
+            type Root<'l> = Root<'l>;
+        }
+
+        // Only the two initial allocations are enough to start a cycle.
+        let pacing = Pacing {
+            trigger_allocations: Some(2),
+            mark_stride: 1,
+            ..Pacing::default()
+        };
+
+        let mut a = Arena::<Root>::new_paced(
+            |mt| {
+                Root(Gc::new(
+                    Node {
+                        child: LockedBox::new(Tree {
+                            leaf: Gc::new(1, mt),
+                        }),
+                    },
+                    mt,
+                ))
+            },
+            pacing,
+        );
+
+        // Start the mark phase, trace the root, and then trace the node.
+        a.run_collection();
+        a.run_collection();
+        a.run_collection();
+
+        a.view(|root, mt| {
+            let old = root.0.write().project(|n| &n.child).replace(Box::new(Tree {
+                leaf: Gc::new(2, mt),
+            }));
+            assert_eq!(*old.leaf, 1);
+        });
+
+        // The old leaf was already marked this cycle.
+        while a.needs_collection() {
+            a.run_collection();
+        }
+        assert_eq!(a.allocations(), 3);
+
+        a.complete_collection();
+        assert_eq!(a.allocations(), 2);
+
+        a.view(|root, _| assert_eq!(*root.0.child.borrow().leaf, 2));
+    }
+
+    #[test]
+    fn swap_children_during_mark() {
+        struct Node<'b> {
+            child: LockedCell<Gc<'b, u32>>,
+        }
+
+        unsafe impl Collect for Node<'_> {
+            const NEEDS_TRACE: bool = true;
+
+            fn trace(&self, c: &Collector) {
+                self.child.trace(c);
+            }
+        }
+
+        struct Pair<'b>(Gc<'b, Node<'b>>, Gc<'b, Node<'b>>);
+
+        unsafe impl Collect for Pair<'_> {
+            const NEEDS_TRACE: bool = true;
+
+            fn trace(&self, c: &Collector) {
+                self.0.trace(c);
+                self.1.trace(c);
+            }
+        }
+
+        impl Rootable for Pair<'static> {
+            type Root<'l> = Pair<'l>;
+        }
+
+        let pacing = Pacing {
+            trigger_allocations: Some(0),
+            mark_stride: 1,
+            ..Pacing::default()
+        };
+        let mut a = Arena::<Pair>::new_paced(
+            |mt| {
+                let node = |i| {
+                    let child = LockedCell::new(Gc::new(i, mt));
+                    Gc::new(Node { child }, mt)
+                };
+                Pair(node(1), node(2))
+            },
+            pacing,
+        );
+
+        // Start marking and trace the root, and then only one of the nodes.
+        a.run_collection();
+        a.run_collection();
+
+        a.view(|root, _| {
+            locked::swap(
+                root.0.write().project(|n| &n.child),
+                root.1.write().project(|n| &n.child),
+            )
+        });
+
+        // Finish the cycle in progress, rather than starting a new one.
+        while a.collect_generation_stats().completed_cycles == 0 {
+            a.run_collection();
+        }
+        assert_eq!(a.allocations(), 4);
+
+        a.view(|root, _| {
+            assert_eq!(*root.0.child.get(), 2);
+            assert_eq!(*root.1.child.get(), 1);
+        });
+    }
+
+    #[test]
+    fn gc_map_environment() {
+        struct Env;
+
+        impl Rootable for Env {
+            type Root<'l> = GcMap<'l, String, str>;
+        }
+
+        let pacing = Pacing {
+            trigger_allocations: Some(0),
+            mark_stride: 1,
+            ..Pacing::default()
+        };
+        let mut arena = Arena::<Env>::new_paced(|_| GcMap::new(), pacing);
+
+        for i in 0..10 {
+            arena.view_mut(|env, mt| {
+                let env = Write::from_mut(env);
+                env.insert(format!("var{i}"), Gc::from_str("value", mt));
+                if i % 2 == 1 {
+                    env.remove(&format!("var{}", i - 1));
+                }
+            });
+            arena.run_collection();
+        }
+
+        arena.complete_collection();
+        arena.view(|env, _| {
+            assert_eq!(env.len(), 5);
+            assert_eq!(env.get("var9").as_deref(), Some("value"));
+            assert!(!env.contains_key("var0"));
+        });
+        assert_eq!(arena.allocations(), 5);
+    }
+
+    #[test]
+    fn gc_weak_map_prunes_dead_entries() {
+        struct Cache;
+
+        impl Rootable for Cache {
+            type Root<'l> = (GcWeakMap<'l, u32, u32>, Vec<Gc<'l, u32>>);
+        }
+
+        let mut arena = Arena::<Cache>::new(|mt| {
+            let (mut cache, mut live) = (GcWeakMap::new(), Vec::new());
+            for i in 0..10 {
+                let value = Gc::new(i, mt);
+                Write::from_mut(&mut cache).insert(i, value);
+                if i % 2 == 0 {
+                    live.push(value);
+                }
+            }
+            (cache, live)
+        });
+
+        arena.complete_collection();
+        assert_eq!(arena.allocations(), 10);
+
+        arena.view(|(cache, _), _| {
+            assert_eq!(cache.len(), 10);
+            assert_eq!(cache.get(&4).as_deref(), Some(&4));
+            assert_eq!(cache.get(&5), None);
+            assert_eq!(cache.len(), 9);
+
+            assert_eq!(cache.prune(), 4);
+            assert_eq!(cache.len(), 5);
+            assert!((0..10).all(|i| cache.get(&i).is_some() == (i % 2 == 0)));
+        });
+
+        // The storage of the dead values is freed once nothing points to them.
+        arena.complete_collection();
+        assert_eq!(arena.allocations(), 5);
+    }
+
+    #[test]
+    fn vec_deque_task_queue_across_steps() {
+        struct Tasks;
+
+        impl Rootable for Tasks {
+            type Root<'l> = Gc<'l, LockedVecDeque<Gc<'l, u32>>>;
+        }
+
+        let pacing = Pacing {
+            trigger_allocations: Some(0),
+            mark_stride: 1,
+            ..Pacing::default()
+        };
+        let mut arena = Arena::<Tasks>::new_paced(|mt| Gc::new(LockedVecDeque::new(), mt), pacing);
+
+        let mut done = Vec::new();
+        for i in 0..20 {
+            // Tasks pushed while the queue is already marked must still be traced.
+            arena.view(|queue, mt| {
+                queue.write().push_back(Gc::new(i, mt));
+                if i % 3 == 2 {
+                    done.push(*queue.write().pop_front().unwrap());
+                }
+            });
+            arena.run_collection();
+        }
+
+        arena.complete_collection();
+        arena.view(|queue, _| {
+            assert_eq!(queue.len(), 14);
+            assert_eq!(queue.front().as_deref(), Some(&6));
+            assert!(queue.borrow().iter().map(|gc| **gc).eq(6..20));
+        });
+        assert_eq!(done, [0, 1, 2, 3, 4, 5]);
+        assert_eq!(arena.allocations(), 15);
+    }
+
+    #[test]
+    fn btree_map_range_after_collection() {
+        struct Env;
+
+        impl Rootable for Env {
+            type Root<'l> = Gc<'l, LockedBTreeMap<u32, Gc<'l, String>>>;
+        }
+
+        let pacing = Pacing {
+            trigger_allocations: Some(0),
+            mark_stride: 1,
+            ..Pacing::default()
+        };
+        let mut arena = Arena::<Env>::new_paced(|mt| Gc::new(LockedBTreeMap::new(), mt), pacing);
+
+        for i in 0..10 {
+            // Entries inserted while the map is already marked must still be traced.
+            arena.view_mut(|env, mt| {
+                env.write().insert(i, Gc::new(i.to_string(), mt));
+                if i % 4 == 3 {
+                    env.write().remove(&(i - 1));
+                }
+            });
+            arena.run_collection();
+        }
+
+        arena.complete_collection();
+        assert_eq!(arena.allocations(), 9);
+        arena.view(|env, _| {
+            assert_eq!(env.len(), 8);
+            assert!(!env.contains_key(&6));
+            assert_eq!(env.get(&9).as_deref().map(String::as_str), Some("9"));
+            let range: Vec<_> = env.borrow().range(3..8).map(|(k, _)| *k).collect();
+            assert_eq!(range, [3, 4, 5, 7]);
+        });
+    }
+
+    #[test]
+    fn generic_locked_collections() {
+        use std::collections::{BTreeMap, BinaryHeap, VecDeque};
+
+        struct Collections;
+
+        impl Rootable for Collections {
+            type Root<'l> = (
+                Gc<'l, Locked<Vec<Gc<'l, u32>>>>,
+                Gc<'l, Locked<VecDeque<Gc<'l, u32>>>>,
+                Gc<'l, Locked<BinaryHeap<Gc<'l, u32>>>>,
+                Gc<'l, Locked<BTreeMap<u32, Gc<'l, u32>>>>,
+            );
+        }
+
+        let pacing = Pacing {
+            trigger_allocations: Some(0),
+            mark_stride: 1,
+            ..Pacing::default()
+        };
+        let mut arena = Arena::<Collections>::new_paced(
+            |mt| {
+                (
+                    Gc::new(Locked::new(), mt),
+                    Gc::new(Locked::new(), mt),
+                    Gc::new(Locked::new(), mt),
+                    Gc::new(Locked::new(), mt),
+                )
+            },
+            pacing,
+        );
+
+        // Every collection is already marked after the first few steps, so each insertion
+        // relies on the barrier.
+        for i in 0..10 {
+            arena.view(|(vec, deque, heap, map), mt| {
+                vec.write().insert(Gc::new(i, mt));
+                deque.write().insert(Gc::new(i, mt));
+                heap.write().insert(Gc::new(i, mt));
+                map.write().insert((i, Gc::new(i, mt)));
+            });
+            arena.run_collection();
+        }
+
+        arena.view(|(vec, deque, heap, map), _| {
+            vec.write().update(|vec| vec.truncate(5));
+            deque.write().update(|deque| deque.pop_front());
+            heap.write().update(|heap| heap.pop());
+            map.write().clear();
+        });
+
+        arena.complete_collection();
+        arena.view(|(vec, deque, heap, map), _| {
+            assert!(vec.borrow().iter().map(|gc| **gc).eq(0..5));
+            assert!(deque.borrow().iter().map(|gc| **gc).eq(1..10));
+            assert_eq!(heap.borrow().peek().map(|gc| **gc), Some(8));
+            assert!(map.is_empty());
+        });
+        assert_eq!(arena.allocations(), 4 + 5 + 9 + 9);
+    }
+}
@@ -0,0 +1,127 @@
+use core::mem::MaybeUninit;
+use std::fmt::Debug;
+
+use crate::{Collect, Collector, Gc, Mutation, UniqueGc};
+
+/// A garbage collected slice which is being filled in, one element at a time.
+///
+/// The slice keeps track of how many of its elements have been initialized, and only traces that
+/// prefix, so it can be stored in the arena and filled in over the course of several collections.
+/// Once every element has been written, [`PartialSlice::finish`] converts it into a regular
+/// [`UniqueGc`], or [`PartialSlice::into_gc`] into a [`Gc`].
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, Gc, PartialSlice};
+/// # once_arena(|mt| {
+/// let mut slice = PartialSlice::new(3, mt);
+///
+/// for i in 0..3 {
+///     slice.push(Gc::new(i, mt)).unwrap();
+/// }
+///
+/// let slice = slice.finish().unwrap();
+/// assert_eq!(*slice[2], 2);
+/// # });
+/// ```
+pub struct PartialSlice<'b, T> {
+    gc: UniqueGc<'b, [MaybeUninit<T>]>,
+    len: usize,
+}
+
+impl<'b, T> PartialSlice<'b, T> {
+    /// Allocates an uninitialized slice with room for `capacity` elements.
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn new(capacity: usize, mt: &Mutation<'b>) -> PartialSlice<'b, T> {
+        PartialSlice {
+            gc: UniqueGc::<[T]>::new_uninit_slice(capacity, mt),
+            len: 0,
+        }
+    }
+
+    /// Writes `val` into the next uninitialized element, returning it back if the slice is
+    /// already full.
+    pub fn push(&mut self, val: T) -> Result<(), T> {
+        match self.gc.get_mut(self.len) {
+            Some(slot) => {
+                slot.write(val);
+                self.len += 1;
+                Ok(())
+            }
+            None => Err(val),
+        }
+    }
+
+    /// Returns the number of initialized elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no elements have been initialized.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the total number of elements in the slice.
+    pub fn capacity(&self) -> usize {
+        self.gc.len()
+    }
+
+    /// Returns the initialized prefix of the slice.
+    pub fn as_slice(&self) -> &[T] {
+        // Safety: The first `len` elements have been written.
+        unsafe { self.gc[..self.len].assume_init_ref() }
+    }
+
+    /// Converts into a [`UniqueGc`], if every element has been initialized, and otherwise returns
+    /// the partial slice back.
+    pub fn finish(self) -> Result<UniqueGc<'b, [T]>, PartialSlice<'b, T>>
+    where
+        T: Collect,
+    {
+        if self.len != self.capacity() {
+            return Err(self);
+        }
+
+        let this = core::mem::ManuallyDrop::new(self);
+        // Safety: `this` is never used again, or dropped.
+        let gc = unsafe { core::ptr::read(&this.gc) };
+        // Safety: Every element has been written.
+        Ok(unsafe { gc.assume_init() })
+    }
+
+    /// Converts into a [`Gc`], if every element has been initialized, and otherwise returns the
+    /// partial slice back.
+    pub fn into_gc(self) -> Result<Gc<'b, [T]>, PartialSlice<'b, T>>
+    where
+        T: Collect,
+    {
+        self.finish().map(UniqueGc::into_gc)
+    }
+}
+
+impl<T> Drop for PartialSlice<'_, T> {
+    fn drop(&mut self) {
+        let len = self.len;
+        // Safety: The first `len` elements have been written, and are never read again.
+        unsafe { self.gc[..len].assume_init_drop() };
+    }
+}
+
+unsafe impl<T: Collect> Collect for PartialSlice<'_, T> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.gc.trace(c);
+        self.as_slice().trace(c);
+    }
+}
+
+impl<T: Debug> Debug for PartialSlice<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PartialSlice")
+            .field("init", &self.as_slice())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
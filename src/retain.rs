@@ -0,0 +1,102 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    hash::{BuildHasher, Hash},
+};
+
+/// A collection from which entries can be removed in place, used by [`Arena::retain_root`].
+///
+/// Each entry is passed to the predicate as a key and a value. Sequences use the position of the
+/// entry as its key, and sets use the element as the key, with a value of `()`.
+///
+/// [`Arena::retain_root`]: crate::Arena::retain_root
+pub trait Retain {
+    /// The key of each entry.
+    type Key: ?Sized;
+    /// The value of each entry.
+    type Value: ?Sized;
+
+    /// Removes every entry for which `f` returns `false`.
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Self::Key, &Self::Value) -> bool;
+}
+
+impl<T> Retain for Vec<T> {
+    type Key = usize;
+    type Value = T;
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&usize, &T) -> bool,
+    {
+        let mut index = 0;
+        Vec::retain(self, |v| {
+            index += 1;
+            f(&(index - 1), v)
+        });
+    }
+}
+
+impl<T> Retain for VecDeque<T> {
+    type Key = usize;
+    type Value = T;
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&usize, &T) -> bool,
+    {
+        let mut index = 0;
+        VecDeque::retain(self, |v| {
+            index += 1;
+            f(&(index - 1), v)
+        });
+    }
+}
+
+impl<T: Eq + Hash, S: BuildHasher> Retain for HashSet<T, S> {
+    type Key = T;
+    type Value = ();
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T, &()) -> bool,
+    {
+        HashSet::retain(self, |k| f(k, &()));
+    }
+}
+
+impl<T: Ord> Retain for BTreeSet<T> {
+    type Key = T;
+    type Value = ();
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T, &()) -> bool,
+    {
+        BTreeSet::retain(self, |k| f(k, &()));
+    }
+}
+
+impl<K, V, S> Retain for HashMap<K, V, S> {
+    type Key = K;
+    type Value = V;
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        HashMap::retain(self, |k, v| f(k, v));
+    }
+}
+
+impl<K: Ord, V> Retain for BTreeMap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        BTreeMap::retain(self, |k, v| f(k, v));
+    }
+}
@@ -0,0 +1,213 @@
+use alloc::rc::{Rc, Weak};
+use std::cell::RefCell;
+
+use crate::{
+    gc_box::{Erased, GcBox},
+    Collect, Collector, Gc, Mutation,
+};
+
+/// A handle to a garbage collected object which keeps it alive, for as long as the handle
+/// isn't dropped.
+///
+/// A `Rooted` isn't branded with the lifetime of its arena, so it can be held outside of any
+/// [`Arena::view`] closure, and across collections. A [`Gc`] to the object can be retrieved
+/// using [`Rooted::get`] within a view of the arena which allocated it.
+///
+/// [`Arena::view`]: crate::Arena::view
+pub struct Rooted<T: ?Sized> {
+    ptr: GcBox<T>,
+    /// Keeps the entry in the root set alive.
+    _handle: Rc<()>,
+    arena: Weak<()>,
+}
+
+impl<T: ?Sized> Rooted<T> {
+    /// Returns a garbage collected pointer to the rooted object.
+    ///
+    /// # Panics
+    /// If the mutation handle does not belong to the arena which allocated the object.
+    pub fn get<'b>(&self, mt: &Mutation<'b>) -> Gc<'b, T> {
+        assert!(
+            self.owned_by(mt),
+            "rooted object was allocated by a different arena"
+        );
+
+        // Safety: The mutation handle belongs to the arena which allocated the object, and the
+        // object has been kept alive by this handle.
+        unsafe { Gc::from_box(self.ptr) }
+    }
+
+    fn owned_by(&self, mt: &Mutation<'_>) -> bool {
+        mt.context().explicit_roots().owns(self)
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for Rooted<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Rooted")
+    }
+}
+
+/// A handle which keeps a garbage collected object alive, and at the same address, for as long
+/// as it isn't dropped, so that a pointer to the object can be handed to foreign code.
+///
+/// Unlike a [`Rooted`] handle, a pin only gives access to the object through a raw pointer, which
+/// is valid for as long as both the pin and the arena exist. The pin doesn't borrow the arena, and
+/// dropping the arena frees every object, including pinned ones. As objects are never moved, it
+/// is the same pointer as [`Gc::as_ptr`] returns.
+pub struct PinGuard<T: ?Sized>(Rooted<T>);
+
+impl<T: ?Sized> PinGuard<T> {
+    pub(crate) fn new(rooted: Rooted<T>) -> PinGuard<T> {
+        PinGuard(rooted)
+    }
+
+    /// Returns a pointer to the pinned object, which is valid until the guard or the arena is
+    /// dropped, whichever happens first.
+    pub fn as_ptr(&self) -> *const T {
+        self.0.ptr.data_ptr().cast_const()
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for PinGuard<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("PinGuard").field(&self.as_ptr()).finish()
+    }
+}
+
+/// A set of named, rooted objects for a `thread_local!`, so that objects can be kept alive, and
+/// found again, from anywhere on the thread, without passing them through every call.
+///
+/// Each object is kept alive by a [`Rooted`] handle, until it is removed or replaced. Objects
+/// from several arenas can be stored in the same set, and are only returned to the arena which
+/// allocated them.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, Gc, ThreadLocalRoots};
+/// thread_local! {
+///     static GLOBALS: ThreadLocalRoots<str> = const { ThreadLocalRoots::new() };
+/// }
+///
+/// once_arena(|mt| {
+///     GLOBALS.with(|globals| globals.insert("greeting", Gc::from_str("hello", mt), mt));
+///
+///     let greeting = GLOBALS.with(|globals| globals.get("greeting", mt)).unwrap();
+///     assert_eq!(&*greeting, "hello");
+/// });
+/// ```
+pub struct ThreadLocalRoots<T: ?Sized> {
+    roots: RefCell<Vec<(&'static str, Rooted<T>)>>,
+}
+
+impl<T: ?Sized> ThreadLocalRoots<T> {
+    pub const fn new() -> ThreadLocalRoots<T> {
+        ThreadLocalRoots {
+            roots: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Roots the object under the given name, replacing any object with the same name from the
+    /// same arena.
+    pub fn insert<'b>(&self, name: &'static str, gc: Gc<'b, T>, mt: &Mutation<'b>) {
+        let rooted = mt.root(gc);
+        let roots = &mut *self.roots.borrow_mut();
+
+        match roots.iter_mut().find(|(n, r)| *n == name && r.owned_by(mt)) {
+            Some((_, slot)) => *slot = rooted,
+            None => roots.push((name, rooted)),
+        }
+    }
+
+    /// Returns the object with the given name, if one was inserted from the same arena.
+    pub fn get<'b>(&self, name: &str, mt: &Mutation<'b>) -> Option<Gc<'b, T>> {
+        self.roots
+            .borrow()
+            .iter()
+            .find(|(n, r)| *n == name && r.owned_by(mt))
+            .map(|(_, r)| r.get(mt))
+    }
+
+    /// Stops rooting the object with the given name from the same arena, returning whether there
+    /// was one.
+    pub fn remove(&self, name: &str, mt: &Mutation<'_>) -> bool {
+        let roots = &mut *self.roots.borrow_mut();
+        let len = roots.len();
+        roots.retain(|(n, r)| !(*n == name && r.owned_by(mt)));
+        roots.len() != len
+    }
+}
+
+impl<T: ?Sized> Default for ThreadLocalRoots<T> {
+    fn default() -> Self {
+        ThreadLocalRoots::new()
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for ThreadLocalRoots<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let names = self.roots.borrow();
+        f.debug_set().entries(names.iter().map(|(n, _)| n)).finish()
+    }
+}
+
+/// The set of objects which have been rooted independently of the arena root.
+pub(crate) struct RootSet {
+    id: Rc<()>,
+    roots: RefCell<Vec<(GcBox<Erased>, Weak<()>)>>,
+}
+
+impl RootSet {
+    pub fn new() -> RootSet {
+        RootSet {
+            id: Rc::new(()),
+            roots: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Registers the box as a root, until the returned handle is dropped.
+    ///
+    /// # Safety
+    /// The box must have been allocated within the context which owns this root set.
+    pub unsafe fn register<T: ?Sized>(&self, ptr: GcBox<T>) -> Rooted<T> {
+        let handle = Rc::new(());
+
+        self.roots
+            .borrow_mut()
+            .push((ptr.erase(), Rc::downgrade(&handle)));
+
+        Rooted {
+            ptr,
+            _handle: handle,
+            arena: Rc::downgrade(&self.id),
+        }
+    }
+
+    fn owns<T: ?Sized>(&self, rooted: &Rooted<T>) -> bool {
+        self.is_id(&rooted.arena)
+    }
+
+    /// Returns a handle which identifies the arena owning this root set, without keeping it
+    /// alive.
+    pub fn id(&self) -> Weak<()> {
+        Rc::downgrade(&self.id)
+    }
+
+    pub fn is_id(&self, id: &Weak<()>) -> bool {
+        core::ptr::eq(Rc::as_ptr(&self.id), id.as_ptr())
+    }
+}
+
+unsafe impl Collect for RootSet {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        let roots = &mut *self.roots.borrow_mut();
+
+        roots.retain(|(_, handle)| handle.strong_count() > 0);
+
+        for (ptr, _) in roots.iter() {
+            c.context().mark_box(*ptr);
+        }
+    }
+}
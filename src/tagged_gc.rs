@@ -0,0 +1,140 @@
+use core::{marker::PhantomData, ptr::NonNull};
+
+use crate::{gc_box::GcBox, Collect, Collector, Gc, Invariant};
+
+/// A [`Gc`] with a small tag packed into the unused low bits of its pointer, as used by
+/// interpreters with tagged value representations.
+///
+/// Garbage collected pointers must never be hidden from the collector by storing their address
+/// as an integer, such as in a `Cell<usize>`, as the collector can't see the integer, and will
+/// free the object it points to. A `TaggedGc` is traced as the pointer it contains, so the
+/// object is kept alive.
+///
+/// # Examples
+/// ```
+/// # use ghost_gc::{once_arena, Gc, TaggedGc};
+/// # once_arena(|mt| {
+/// const STRING: usize = 1;
+///
+/// let value = TaggedGc::new(Gc::from_str("hello", mt), STRING);
+/// assert_eq!(value.tag(), STRING);
+/// assert_eq!(&*value.gc(), "hello");
+/// # });
+/// ```
+pub struct TaggedGc<'b, T: ?Sized>(NonNull<()>, Invariant<'b>, PhantomData<*const T>);
+
+impl<'b, T: ?Sized> TaggedGc<'b, T> {
+    /// The number of bits available for the tag, which only depends on the alignment of a
+    /// pointer, as every allocation starts with a pointer-aligned header.
+    pub const TAG_BITS: u32 = align_of::<usize>().trailing_zeros();
+
+    const TAG_MASK: usize = (1 << Self::TAG_BITS) - 1;
+
+    /// Packs the tag into the pointer.
+    ///
+    /// # Panics
+    /// If the tag doesn't fit in [`TaggedGc::TAG_BITS`] bits.
+    pub fn new(gc: Gc<'b, T>, tag: usize) -> TaggedGc<'b, T> {
+        assert!(
+            tag <= Self::TAG_MASK,
+            "tag {tag} doesn't fit in {} bits",
+            Self::TAG_BITS
+        );
+
+        let ptr = gc.into_box().into_raw();
+        debug_assert_eq!(ptr.addr().get() & Self::TAG_MASK, 0);
+
+        TaggedGc(ptr.map_addr(|addr| addr | tag), Invariant, PhantomData)
+    }
+
+    /// Returns the pointer, without its tag.
+    pub fn gc(self) -> Gc<'b, T> {
+        let ptr = self.0.map_addr(|addr| {
+            // Safety: The tag is only ever in the low bits of a non-null, aligned address.
+            unsafe { core::num::NonZeroUsize::new_unchecked(addr.get() & !Self::TAG_MASK) }
+        });
+
+        // Safety: The untagged pointer came from a `Gc` with the same brand.
+        unsafe { Gc::from_box(GcBox::from_raw(ptr)) }
+    }
+
+    pub fn tag(self) -> usize {
+        self.0.addr().get() & Self::TAG_MASK
+    }
+
+    /// Returns the same pointer with a different tag.
+    ///
+    /// # Panics
+    /// If the tag doesn't fit in [`TaggedGc::TAG_BITS`] bits.
+    pub fn with_tag(self, tag: usize) -> TaggedGc<'b, T> {
+        TaggedGc::new(self.gc(), tag)
+    }
+}
+
+impl<T: ?Sized> Clone for TaggedGc<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for TaggedGc<'_, T> {}
+
+impl<T: ?Sized + core::fmt::Debug> core::fmt::Debug for TaggedGc<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TaggedGc")
+            .field("tag", &self.tag())
+            .field("value", &self.gc())
+            .finish()
+    }
+}
+
+unsafe impl<'b, T: ?Sized> Collect for TaggedGc<'b, T> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.gc().trace(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Arena, Gc, Rootable, TaggedGc};
+
+    #[test]
+    fn tagged_values_survive_collection() {
+        struct Values;
+
+        impl Rootable for Values {
+            type Root<'l> = Vec<TaggedGc<'l, str>>;
+        }
+
+        let max_tag = (1 << TaggedGc::<str>::TAG_BITS) - 1;
+        let mut a = Arena::<Values>::new(|mt| {
+            let _garbage = Gc::from_str("garbage", mt);
+            vec![
+                TaggedGc::new(Gc::from_str("zero", mt), 0),
+                TaggedGc::new(Gc::from_str("max", mt), max_tag),
+            ]
+        });
+
+        a.complete_collection();
+        assert_eq!(a.allocations(), 2);
+
+        a.view(|values, _| {
+            for (value, expected) in values.iter().zip([(0, "zero"), (max_tag, "max")]) {
+                assert_eq!((value.tag(), &*value.gc()), expected);
+            }
+
+            let retagged = values[0].with_tag(1);
+            assert_eq!((retagged.tag(), &*retagged.gc()), (1, "zero"));
+        });
+    }
+
+    #[test]
+    #[should_panic = "doesn't fit"]
+    fn tag_too_large() {
+        crate::once_arena(|mt| {
+            TaggedGc::new(Gc::new(0u8, mt), 1 << TaggedGc::<u8>::TAG_BITS);
+        });
+    }
+}
@@ -1,12 +1,16 @@
 use core::{
-    alloc::Layout,
+    alloc::{AllocError, Layout},
+    marker::Unsize,
     mem::MaybeUninit,
     ops::{Deref, DerefMut},
     ptr::Pointee,
 };
-use std::fmt::Debug;
+use std::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+};
 
-use crate::{context::Mutation, gc::Gc, gc_box::GcBox, Collect, Invariant};
+use crate::{context::Mutation, gc::Gc, gc_box::GcBox, Collect, DeepClone, Invariant};
 
 /// A thin, garbage collected pointer type, which is guaranteed to be unique.
 pub struct UniqueGc<'b, T: ?Sized>(GcBox<T>, Invariant<'b>);
@@ -24,6 +28,7 @@ impl<'b, T> UniqueGc<'b, T> {
     /// let five = UniqueGc::new(5, mt);
     /// # });
     /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub fn new(val: T, mt: &Mutation<'b>) -> UniqueGc<'b, T>
     where
         T: Collect,
@@ -31,6 +36,7 @@ impl<'b, T> UniqueGc<'b, T> {
         let inner = mt.allocate::<T>((), Layout::new::<T>());
         // .context()
         // .allocate::<T>((), Layout::new::<T>(), mt.alloc());
+        debug_assert!(inner.data_ptr().is_aligned());
         // Safety: No references exist, as the pointer was just created.
         unsafe { inner.data_ptr().write(val) };
         // Safety: The value was just written.
@@ -39,6 +45,89 @@ impl<'b, T> UniqueGc<'b, T> {
         UniqueGc(inner, Invariant)
     }
 
+    /// Allocates `val` as with [`UniqueGc::new_unsizable`], recording its [`DeepClone`]
+    /// implementation alongside it, so that it can be deep cloned through a pointer to an unsized
+    /// type, with [`DeepCloner::clone_unsized`].
+    ///
+    /// [`DeepCloner::clone_unsized`]: crate::DeepCloner::clone_unsized
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn new_cloneable(val: T, mt: &Mutation<'b>) -> UniqueGc<'b, T>
+    where
+        T: Collect + DeepClone,
+    {
+        let this = UniqueGc::new_unsizable(val, mt);
+        // Safety: The box contains a `T`.
+        unsafe { this.0.set_cloneable() };
+        this
+    }
+
+    /// Allocates `val` as with [`UniqueGc::new`], with room for the metadata of a pointer to an
+    /// unsized form of it, so that [`UniqueGc::coerce`] doesn't need to move it.
+    ///
+    /// Allocations made with [`UniqueGc::new`] don't have this room, which is the size of a
+    /// pointer.
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn new_unsizable(val: T, mt: &Mutation<'b>) -> UniqueGc<'b, T>
+    where
+        T: Collect,
+    {
+        let inner = mt.context().allocate_unsizable::<T>();
+        // Safety: No references exist, as the pointer was just created.
+        unsafe { inner.data_ptr().write(val) };
+        // Safety: The value was just written.
+        unsafe { inner.set_init() };
+
+        UniqueGc(inner, Invariant)
+    }
+
+    /// Converts the pointer into a pointer to an unsized type which `T` can be coerced to, such
+    /// as a trait object.
+    ///
+    /// The metadata of the unsized type is stored in the allocation, so if the value wasn't
+    /// allocated with [`UniqueGc::new_unsizable`], it is first moved into an allocation which has
+    /// room for it. This only takes a `UniqueGc`, as every pointer to an unsized form of an
+    /// object has to agree on its metadata.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{once_arena, UniqueGc, Gc};
+    /// # use core::fmt::Display;
+    /// # once_arena(|mt| {
+    /// let five = UniqueGc::new_unsizable(5, mt);
+    /// let display: Gc<dyn Display> = UniqueGc::into_gc(UniqueGc::coerce(five, mt));
+    /// assert_eq!(display.to_string(), "5");
+    /// # });
+    /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn coerce<U: ?Sized>(this: Self, mt: &Mutation<'b>) -> UniqueGc<'b, U>
+    where
+        T: Collect + Unsize<U>,
+    {
+        let inner = if this.0.is_unsizable() {
+            this.0
+        } else {
+            let inner = mt.context().allocate_unsizable::<T>();
+            // Safety: The value is moved, and the old box is left uninitialized, so that it is
+            // never dropped, and is freed once it is swept. Nothing else points to it, as the
+            // pointer is unique.
+            unsafe {
+                inner
+                    .data_ptr()
+                    .copy_from_nonoverlapping(this.0.data_ptr(), 1);
+                inner.copy_vtable(this.0);
+                inner.set_init();
+            }
+            this.0.set_uninit();
+            inner
+        };
+
+        let ptr: *const U = inner.data_ptr().cast_const();
+        // Safety: The value is valid as a `U` with the metadata of the coercion, and the vtable
+        // continues to trace and drop it as a `T`.
+        let inner = unsafe { inner.unsize::<U>(core::ptr::metadata(ptr)) };
+        UniqueGc(inner, Invariant)
+    }
+
     /// Constructs a new garbage collected pointer with uninitialized contents.
     ///
     /// # Examples
@@ -56,6 +145,7 @@ impl<'b, T> UniqueGc<'b, T> {
     /// assert_eq!(*five, 5);
     /// # });
     /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub fn new_uninit(mt: &Mutation<'b>) -> UniqueGc<'b, MaybeUninit<T>> {
         UniqueGc::new(MaybeUninit::uninit(), mt)
     }
@@ -76,6 +166,7 @@ impl<'b, T> UniqueGc<'b, T> {
     /// assert_eq!(*zero, 0);
     /// # });
     /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub fn new_zeroed(mt: &Mutation<'b>) -> UniqueGc<'b, MaybeUninit<T>> {
         UniqueGc::new(MaybeUninit::zeroed(), mt)
     }
@@ -101,6 +192,7 @@ impl<'b, T> UniqueGc<'b, [T]> {
     /// assert_eq!(*values, [1, 2, 3]);
     /// # });
     /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub fn new_uninit_slice(len: usize, mt: &Mutation<'b>) -> UniqueGc<'b, [MaybeUninit<T>]> {
         let inner = mt
             .context()
@@ -111,6 +203,81 @@ impl<'b, T> UniqueGc<'b, [T]> {
         UniqueGc(inner, Invariant)
     }
 
+    /// Constructs a new garbage collected slice with uninitialized contents, returning an error
+    /// if the slice is too large, or if the allocation fails.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{once_arena, UniqueGc};
+    /// # once_arena(|mt| {
+    /// assert!(UniqueGc::<[u64]>::try_new_uninit_slice(usize::MAX, mt).is_err());
+    /// assert!(UniqueGc::<[u64]>::try_new_uninit_slice(isize::MAX as usize / 8, mt).is_err());
+    /// assert!(UniqueGc::<[u64]>::try_new_uninit_slice(16, mt).is_ok());
+    /// # });
+    /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn try_new_uninit_slice(
+        len: usize,
+        mt: &Mutation<'b>,
+    ) -> Result<UniqueGc<'b, [MaybeUninit<T>]>, AllocError> {
+        let layout = Layout::array::<T>(len).map_err(|_| AllocError)?;
+        let inner = mt.try_allocate::<[MaybeUninit<T>]>(len, layout)?;
+
+        unsafe { inner.set_init() };
+
+        Ok(UniqueGc(inner, Invariant))
+    }
+
+    /// Constructs a new garbage collected slice, cloned from the elements of `src`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{once_arena, UniqueGc};
+    /// # once_arena(|mt| {
+    /// let values = UniqueGc::from_slice(&[1, 2, 3], mt);
+    /// assert_eq!(*values, [1, 2, 3]);
+    /// # });
+    /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn from_slice(src: &[T], mt: &Mutation<'b>) -> UniqueGc<'b, [T]>
+    where
+        T: Clone + Collect,
+    {
+        let mut gc = UniqueGc::<[T]>::new_uninit_slice(src.len(), mt);
+        UniqueGc::write_slice(&mut gc, src);
+        unsafe { gc.assume_init() }
+    }
+
+    /// Constructs a new garbage collected slice, cloned from the elements of `src`, returning an
+    /// error if the allocation fails.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{once_arena, UniqueGc};
+    /// # once_arena(|mt| {
+    /// let values = UniqueGc::try_from_slice(&[1, 2, 3], mt).unwrap();
+    /// assert_eq!(*values, [1, 2, 3]);
+    /// # });
+    /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn try_from_slice(src: &[T], mt: &Mutation<'b>) -> Result<UniqueGc<'b, [T]>, AllocError>
+    where
+        T: Clone + Collect,
+    {
+        let mut gc = UniqueGc::<[T]>::try_new_uninit_slice(src.len(), mt)?;
+        UniqueGc::write_slice(&mut gc, src);
+        Ok(unsafe { gc.assume_init() })
+    }
+
+    fn write_slice(gc: &mut UniqueGc<'b, [MaybeUninit<T>]>, src: &[T])
+    where
+        T: Clone,
+    {
+        for (slot, value) in gc.iter_mut().zip(src) {
+            slot.write(value.clone());
+        }
+    }
+
     /// Constructs a new garbage collected slice with uninitialized contents, with the memory being
     /// filled with `0` bytes.
     ///
@@ -126,6 +293,7 @@ impl<'b, T> UniqueGc<'b, [T]> {
     /// assert_eq!(*values, [0, 0, 0]);
     /// # });
     /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub fn new_zeroed_slice(len: usize, mt: &Mutation<'b>) -> UniqueGc<'b, [MaybeUninit<T>]> {
         let inner = mt
             .context()
@@ -137,6 +305,53 @@ impl<'b, T> UniqueGc<'b, [T]> {
 
         UniqueGc(inner, Invariant)
     }
+
+    /// Splits the slice into two at the given index, moving the elements `[at, len)` into a new
+    /// garbage collected slice.
+    ///
+    /// Afterwards `self` contains the elements `[0, at)`. The memory of the original allocation
+    /// isn't shrunk, and is freed as a whole once it is collected.
+    ///
+    /// # Panics
+    /// If `at > len`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ghost_gc::{once_arena, UniqueGc};
+    /// # once_arena(|mt| {
+    /// let mut values = UniqueGc::from_slice(&[1, 2, 3], mt);
+    /// let tail = values.split_off(1, mt);
+    ///
+    /// assert_eq!(*values, [1]);
+    /// assert_eq!(*tail, [2, 3]);
+    /// # });
+    /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
+    pub fn split_off(&mut self, at: usize, mt: &Mutation<'b>) -> UniqueGc<'b, [T]>
+    where
+        T: Collect,
+    {
+        let len = self.len();
+        assert!(
+            at <= len,
+            "`at` split index (is {at}) should be <= len (is {len})"
+        );
+
+        let tail = UniqueGc::<[T]>::new_uninit_slice(len - at, mt);
+
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.as_ptr().add(at),
+                tail.0.data_ptr().cast::<T>(),
+                len - at,
+            );
+        }
+
+        // The moved elements are no longer part of `self`, so they won't be dropped twice.
+        self.0 = unsafe { self.0.unsize::<[T]>(at) };
+
+        unsafe { tail.assume_init() }
+    }
 }
 
 impl<'b> UniqueGc<'b, str> {
@@ -149,6 +364,7 @@ impl<'b> UniqueGc<'b, str> {
     /// assert_eq!(&*s, "Hello, World!");
     /// # });
     /// ```
+    #[cfg_attr(feature = "debug-alloc-tags", track_caller)]
     pub fn from_str(s: &str, mt: &Mutation<'b>) -> UniqueGc<'b, str> {
         let mut gc = UniqueGc::<[u8]>::new_uninit_slice(s.len(), mt);
 
@@ -283,6 +499,7 @@ impl<T: ?Sized> Deref for UniqueGc<'_, T> {
 
 impl<T: ?Sized> DerefMut for UniqueGc<'_, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.write_barrier();
         unsafe { self.0.data_mut() }
     }
 }
@@ -291,16 +508,19 @@ unsafe impl<'b, T: ?Sized> Collect for UniqueGc<'b, T> {
     const NEEDS_TRACE: bool = true;
 
     fn trace(&self, c: &crate::Collector) {
-        use crate::gc_box::Colour;
+        c.context().mark_box(self.0);
+    }
+}
 
-        match self.0.colour() {
-            Colour::Gray | Colour::White | Colour::Weak => {
-                unsafe { self.0.set_colour(Colour::Gray) };
+impl<T: ?Sized> Borrow<T> for UniqueGc<'_, T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
 
-                c.context().push_box(self.0.erase());
-            }
-            Colour::Black => {}
-        }
+impl<T: ?Sized> AsRef<T> for UniqueGc<'_, T> {
+    fn as_ref(&self) -> &T {
+        self
     }
 }
 
@@ -310,6 +530,12 @@ impl<T: ?Sized + Debug> Debug for UniqueGc<'_, T> {
     }
 }
 
+impl<T: ?Sized + Display> Display for UniqueGc<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
 impl<T: ?Sized + PartialEq> PartialEq for UniqueGc<'_, T> {
     fn eq(&self, other: &Self) -> bool {
         (**self) == (**other)
@@ -22,6 +22,11 @@ impl<T: ?Sized> Write<T> {
         unsafe { Write::new_unchecked(value) }
     }
 
+    /// Mutable access is always exclusive of the collector, so it also grants write permission.
+    pub fn from_mut(value: &mut T) -> &Write<T> {
+        unsafe { Write::new_unchecked(value) }
+    }
+
     pub fn into_inner(&self) -> &T {
         &self.0
     }
@@ -0,0 +1,1193 @@
+use ghost_gc::{
+    locked::{LockedCell, LockedRefCell},
+    Arena, Collect, CollectionCursor, Collector, CursorPhase, DeepClone, DeepCloner, Gc,
+    GenerationStats, Mutation, Pacing, Rootable, ThreadLocalRoots, UniqueGc,
+};
+
+/// A root which holds nothing, so that every allocation is garbage.
+struct EmptyRoot;
+
+unsafe impl Collect for EmptyRoot {
+    const NEEDS_TRACE: bool = false;
+
+    fn trace(&self, _c: &Collector) {}
+}
+
+impl Rootable for EmptyRoot {
+    type Root<'l> = EmptyRoot;
+}
+
+#[test]
+fn stats_by_type() {
+    let a = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+
+    a.view(|_, mt| {
+        for i in 0..3u32 {
+            let _ = Gc::new(i, mt);
+        }
+
+        for _ in 0..2 {
+            let _ = Gc::new([0u64; 4], mt);
+        }
+    });
+
+    let stats = a.stats_by_type();
+    assert_eq!(stats.len(), 2);
+
+    let small = stats[core::any::type_name::<u32>()];
+    let large = stats[core::any::type_name::<[u64; 4]>()];
+
+    assert_eq!(small.count, 3);
+    assert_eq!(large.count, 2);
+    assert_eq!(
+        large.bytes / large.count - small.bytes / small.count,
+        32 - 8
+    );
+    assert_eq!((small.size, small.align), (4, 4));
+    assert_eq!((large.size, large.align), (32, 8));
+}
+
+#[test]
+fn rooted_allocations() {
+    let mut a = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+
+    let mut rooted: Vec<_> = (0..6u32).map(|i| a.alloc(i)).collect();
+    assert_eq!(a.allocations(), 6);
+
+    // Drop every value which is odd.
+    rooted.retain(|r| a.view(|_, mt| *r.get(mt) % 2 == 0));
+
+    a.complete_collection();
+    assert_eq!(a.allocations(), 3);
+
+    a.view(|_, mt| {
+        let values: Vec<u32> = rooted.iter().map(|r| *r.get(mt)).collect();
+        assert_eq!(values, [0, 2, 4]);
+    });
+
+    drop(rooted);
+    a.complete_collection();
+    assert_eq!(a.allocations(), 0);
+}
+
+#[test]
+#[should_panic]
+fn rooted_wrong_arena() {
+    let mut a = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+    let b = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+
+    let rooted = a.alloc(5u32);
+    b.view(|_, mt| {
+        let _ = rooted.get(mt);
+    });
+}
+
+#[test]
+fn view_counted() {
+    let a = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+
+    let (sum, count) = a.view_counted(|_, mt| (0..7u32).map(|i| *Gc::new(i, mt)).sum::<u32>());
+    assert_eq!(sum, 21);
+    assert_eq!(count, 7);
+
+    let ((), count) = a.view_counted(|_, _| {});
+    assert_eq!(count, 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn assert_collected() {
+    let mut a = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+
+    let rooted = a.alloc(1u32);
+    a.view(|_, mt| {
+        let _ = Gc::new(2u32, mt);
+    });
+
+    a.assert_collected(1);
+    drop(rooted);
+    a.assert_collected(0);
+}
+
+#[test]
+#[cfg(all(debug_assertions, not(feature = "debug-alloc-tags")))]
+#[should_panic = "expected 0 live objects after collection, found 1: [1 x u32]"]
+fn assert_collected_leak() {
+    let mut a = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+
+    let _rooted = a.alloc(1u32);
+    a.assert_collected(0);
+}
+
+#[test]
+#[cfg(all(debug_assertions, feature = "debug-alloc-tags"))]
+fn assert_collected_leak_site() {
+    let mut a = Arena::<HalfRoot>::new(|_| Vec::new());
+
+    let line = a.view_mut(|stack, mt| {
+        stack.push(Gc::new(1, mt));
+        line!() - 1
+    });
+
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| a.assert_collected(0)))
+        .unwrap_err();
+    let message = err.downcast_ref::<String>().unwrap();
+    let site = format!("1 x u32 allocated at {}:{line}:", file!());
+    assert!(message.contains(&site), "{message}");
+}
+
+#[test]
+fn needs_collection() {
+    let pacing = Pacing {
+        trigger_allocations: Some(10),
+        trigger_bytes: None,
+        ..Pacing::default()
+    };
+    let mut a = Arena::<EmptyRoot>::new_paced(|_| EmptyRoot, pacing);
+    assert!(!a.needs_collection());
+
+    a.view(|_, mt| {
+        for i in 0..9u32 {
+            let _ = Gc::new(i, mt);
+        }
+    });
+    assert!(!a.needs_collection());
+
+    a.view(|_, mt| {
+        let _ = Gc::new(9u32, mt);
+    });
+    assert!(a.needs_collection());
+
+    while a.needs_collection() {
+        a.run_collection();
+    }
+    assert_eq!(a.allocations(), 0);
+    assert!(!a.needs_collection());
+
+    a.view(|_, mt| {
+        let _ = Gc::new(0u32, mt);
+    });
+    a.complete_collection();
+    assert!(!a.needs_collection());
+}
+
+struct CacheRoot;
+
+impl Rootable for CacheRoot {
+    type Root<'l> = std::collections::HashMap<u32, Gc<'l, [u8]>>;
+}
+
+#[test]
+fn retain_root_evicts_entries() {
+    let mut a = Arena::<CacheRoot>::new(|mt| {
+        (0..100)
+            .map(|i| {
+                (
+                    i,
+                    UniqueGc::into_gc(UniqueGc::from_slice(&[i as u8; 16], mt)),
+                )
+            })
+            .collect()
+    });
+    assert_eq!(a.allocations(), 100);
+
+    a.retain_root(|key, _| key % 2 == 0);
+    assert_eq!(a.allocations(), 50);
+
+    a.view(|root, _| {
+        assert_eq!(root.len(), 50);
+        assert!(root
+            .iter()
+            .all(|(key, value)| key % 2 == 0 && value[0] == *key as u8));
+    });
+}
+
+struct CycleNode<'b> {
+    value: LockedCell<u32>,
+    next: LockedCell<Option<Gc<'b, CycleNode<'b>>>>,
+}
+
+unsafe impl Collect for CycleNode<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.next.trace(c);
+    }
+}
+
+unsafe impl DeepClone for CycleNode<'_> {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        CycleNode {
+            value: self.value.deep_clone(cx),
+            next: self.next.deep_clone(cx),
+        }
+    }
+}
+
+struct CycleRoot;
+
+impl Rootable for CycleRoot {
+    type Root<'l> = Gc<'l, CycleNode<'l>>;
+}
+
+fn cycle_values<'b>(start: Gc<'b, CycleNode<'b>>) -> Vec<u32> {
+    let mut values = vec![start.value.get()];
+    let mut node = start.next.get().unwrap();
+    while !Gc::ptr_eq(node, start) {
+        values.push(node.value.get());
+        node = node.next.get().unwrap();
+    }
+    values
+}
+
+#[test]
+fn clone_cyclic_graph() {
+    let original = Arena::<CycleRoot>::new(|mt| {
+        let first = Gc::new(
+            CycleNode {
+                value: LockedCell::new(0),
+                next: LockedCell::new(None),
+            },
+            mt,
+        );
+        let mut last = first;
+        for i in 1..5 {
+            let node = Gc::new(
+                CycleNode {
+                    value: LockedCell::new(i),
+                    next: LockedCell::new(Some(last)),
+                },
+                mt,
+            );
+            last = node;
+        }
+        first.write().project(|n| &n.next).unlock().set(Some(last));
+        first
+    });
+
+    let mut copy = original.clone();
+    assert_eq!(copy.allocations(), 5);
+
+    copy.view(|root, _| {
+        let mut node = *root;
+        for _ in 0..5 {
+            node.write()
+                .project(|n| &n.value)
+                .unlock()
+                .set(node.value.get() * 10);
+            node = node.next.get().unwrap();
+        }
+    });
+    copy.complete_collection();
+    assert_eq!(copy.allocations(), 5);
+
+    let copied = copy.view(|root, _| (root.as_ptr().addr(), cycle_values(*root)));
+    let kept = original.view(|root, _| (root.as_ptr().addr(), cycle_values(*root)));
+
+    assert_ne!(copied.0, kept.0);
+    assert_eq!(kept.1, [0, 4, 3, 2, 1]);
+    assert_eq!(copied.1, [0, 40, 30, 20, 10]);
+}
+
+trait Shape<'b> {
+    fn area(&self) -> u32;
+
+    fn parts(&self) -> Vec<Gc<'b, dyn Shape<'b> + 'b>>;
+}
+
+struct Square(u32);
+
+unsafe impl Collect for Square {
+    const NEEDS_TRACE: bool = false;
+
+    fn trace(&self, _c: &Collector) {}
+}
+
+unsafe impl DeepClone for Square {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        Square(self.0.deep_clone(cx))
+    }
+}
+
+impl<'b> Shape<'b> for Square {
+    fn area(&self) -> u32 {
+        self.0 * self.0
+    }
+
+    fn parts(&self) -> Vec<Gc<'b, dyn Shape<'b> + 'b>> {
+        Vec::new()
+    }
+}
+
+struct Group<'b>(Vec<Gc<'b, dyn Shape<'b> + 'b>>);
+
+unsafe impl Collect for Group<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.0.trace(c);
+    }
+}
+
+unsafe impl DeepClone for Group<'_> {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        Group(self.0.iter().map(|part| cx.clone_unsized(*part)).collect())
+    }
+}
+
+impl<'b> Shape<'b> for Group<'b> {
+    fn area(&self) -> u32 {
+        self.0.iter().map(|part| part.area()).sum()
+    }
+
+    fn parts(&self) -> Vec<Gc<'b, dyn Shape<'b> + 'b>> {
+        self.0.clone()
+    }
+}
+
+struct ShapeRoot<'b>(Gc<'b, dyn Shape<'b> + 'b>);
+
+unsafe impl Collect for ShapeRoot<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.0.trace(c);
+    }
+}
+
+unsafe impl DeepClone for ShapeRoot<'_> {
+    fn deep_clone(&self, cx: &DeepCloner<'_>) -> Self {
+        ShapeRoot(cx.clone_unsized(self.0))
+    }
+}
+
+impl Rootable for ShapeRoot<'static> {
+    type Root<'l> = ShapeRoot<'l>;
+}
+
+/// Allocates a shape which can be deep cloned through a `Gc<dyn Shape>`.
+fn shape<'b, T: Shape<'b> + Collect + DeepClone + 'b>(
+    value: T,
+    mt: &Mutation<'b>,
+) -> Gc<'b, dyn Shape<'b> + 'b> {
+    UniqueGc::into_gc(UniqueGc::coerce(UniqueGc::new_cloneable(value, mt), mt))
+}
+
+#[test]
+fn clone_trait_object_graph() {
+    let original = Arena::<ShapeRoot>::new(|mt| {
+        let shared = shape(Square(2), mt);
+        let inner = shape(Group(vec![shared, shared]), mt);
+        ShapeRoot(shape(Group(vec![inner, shared]), mt))
+    });
+
+    let mut copy = original.clone();
+    copy.complete_collection();
+    assert_eq!(copy.allocations(), 3);
+
+    copy.view(|root, _| {
+        assert_eq!(root.0.area(), 12);
+
+        // The square is shared between both groups in the copy, as in the original.
+        let parts = root.0.parts();
+        let inner = parts[0].parts();
+        assert!(Gc::ptr_eq(inner[0], inner[1]));
+        assert!(Gc::ptr_eq(inner[0], parts[1]));
+
+        let original_square = original.view(|root, _| root.0.parts()[1].as_ptr().addr());
+        assert_ne!(parts[1].as_ptr().addr(), original_square);
+    });
+
+    // The copies are themselves cloneable through their vtables.
+    let second = copy.clone();
+    second.view(|root, _| assert_eq!(root.0.area(), 12));
+}
+
+#[test]
+#[should_panic = "was not allocated with `UniqueGc::new_cloneable`"]
+fn clone_trait_object_without_clone_slot() {
+    let original = Arena::<ShapeRoot>::new(|mt| {
+        let square = UniqueGc::new(Square(1), mt);
+        ShapeRoot(UniqueGc::into_gc(UniqueGc::coerce(square, mt)))
+    });
+    let _copy = original.clone();
+}
+
+#[test]
+fn resume_collection_from_cursor() {
+    let pacing = Pacing {
+        trigger_allocations: Some(0),
+        mark_stride: 1,
+        ..Pacing::default()
+    };
+    let mut a = Arena::<HalfRoot>::new_paced(|mt| (0..5).map(|i| Gc::new(i, mt)).collect(), pacing);
+    a.view(|_, mt| {
+        for i in 0..3 {
+            let _garbage = Gc::new(100 + i, mt);
+        }
+    });
+
+    // Wake the collector, then trace a single object.
+    a.run_collection();
+    a.run_collection();
+    let cursor = a.save_collection_cursor();
+    assert!(matches!(cursor.phase, CursorPhase::Mark { gray } if gray > 0));
+
+    // Unrelated work, which modifies the root after it has already been traced.
+    a.view_mut(|stack, mt| stack.push(Gc::new(5, mt)));
+
+    a.resume_collection(cursor);
+    assert_eq!(
+        a.save_collection_cursor(),
+        CollectionCursor {
+            cycle: cursor.cycle + 1,
+            phase: CursorPhase::Sleep
+        }
+    );
+    assert_eq!(a.allocations(), 6);
+
+    // The cycle has already finished, so a stale cursor does nothing.
+    a.resume_collection(cursor);
+    assert_eq!(a.collect_generation_stats().completed_cycles, 1);
+    a.view(|stack, _| assert!(stack.iter().map(|gc| **gc).eq(0..6)));
+}
+
+thread_local! {
+    static COERCED_DROPS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// A value which counts how many times it has been dropped.
+struct Coerced;
+
+unsafe impl Collect for Coerced {
+    const NEEDS_TRACE: bool = false;
+
+    fn trace(&self, _c: &Collector) {}
+}
+
+impl Drop for Coerced {
+    fn drop(&mut self) {
+        COERCED_DROPS.set(COERCED_DROPS.get() + 1);
+    }
+}
+
+#[test]
+fn coerce_moves_value_without_room() {
+    let mut arena = Arena::<EmptyRoot>::new(|mt| {
+        let any = UniqueGc::coerce::<dyn std::any::Any>(UniqueGc::new(Coerced, mt), mt);
+        assert!(any.is::<Coerced>());
+        EmptyRoot
+    });
+    assert_eq!(arena.allocations(), 2);
+
+    // The value is only dropped once, even though it was allocated twice.
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 0);
+    assert_eq!(COERCED_DROPS.get(), 1);
+}
+
+thread_local! {
+    static ARMED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// A value whose `trace` panics while armed.
+struct Bomb;
+
+unsafe impl Collect for Bomb {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, _c: &Collector) {
+        if ARMED.get() {
+            panic!("bomb traced");
+        }
+    }
+}
+
+struct BombRoot;
+
+impl Rootable for BombRoot {
+    type Root<'l> = Vec<Gc<'l, Bomb>>;
+}
+
+#[test]
+fn panicking_trace_aborts_cycle() {
+    let mut a = Arena::<BombRoot>::new(|mt| {
+        let _garbage = Gc::new(0u32, mt);
+        (0..5).map(|_| Gc::new(Bomb, mt)).collect()
+    });
+
+    ARMED.set(true);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| a.complete_collection()));
+    assert!(result.is_err());
+    assert_eq!(a.allocations(), 6);
+
+    ARMED.set(false);
+    a.complete_collection();
+    assert_eq!(a.allocations(), 5);
+}
+
+struct HalfRoot;
+
+impl Rootable for HalfRoot {
+    type Root<'l> = Vec<Gc<'l, u32>>;
+}
+
+#[test]
+fn generation_stats_reclaim_ratio() {
+    let mut a = Arena::<HalfRoot>::new(|mt| {
+        (0..50)
+            .map(|i| {
+                let _garbage = Gc::new(i, mt);
+                Gc::new(i, mt)
+            })
+            .collect()
+    });
+    assert_eq!(a.collect_generation_stats(), GenerationStats::default());
+
+    a.complete_collection();
+    let stats = a.collect_generation_stats();
+    assert_eq!(stats.completed_cycles, 1);
+    assert_eq!((stats.freed, stats.survived), (50, 50));
+    assert_eq!(stats.reclaim_ratio(), 0.5);
+    assert_eq!(stats.survived_per_cycle(), 50.0);
+    assert!(stats.average_gray_len() > 0.0);
+}
+
+struct Wide<'b> {
+    fields: [Option<Gc<'b, u32>>; 8],
+    count: usize,
+}
+
+unsafe impl Collect for Wide<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.fields.trace(c);
+    }
+}
+
+struct WideRoot<'b> {
+    holder: Gc<'b, LockedRefCell<Vec<Gc<'b, u32>>>>,
+    wide: Gc<'b, Wide<'b>>,
+}
+
+unsafe impl Collect for WideRoot<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.holder.trace(c);
+        self.wide.trace(c);
+    }
+}
+
+impl Rootable for WideRoot<'static> {
+    type Root<'l> = WideRoot<'l>;
+}
+
+#[test]
+fn with_unique_applies_one_barrier() {
+    let pacing = Pacing {
+        trigger_allocations: Some(0),
+        mark_stride: 1,
+        ..Pacing::default()
+    };
+    let mut a = Arena::<WideRoot>::new_paced(
+        |mt| WideRoot {
+            holder: Gc::new(
+                LockedRefCell::new((0..8).map(|i| Gc::new(i, mt)).collect()),
+                mt,
+            ),
+            wide: Gc::new(
+                Wide {
+                    fields: [None; 8],
+                    count: 0,
+                },
+                mt,
+            ),
+        },
+        pacing,
+    );
+
+    // Starts marking, and then marks `wide` black, while `holder` and everything it points to
+    // is still unmarked.
+    a.run_collection();
+    a.run_collection();
+
+    a.view(|root, mt| {
+        let values: Vec<_> = root.holder.unlock().borrow_mut().drain(..).collect();
+
+        // Safety: The root's pointer isn't used until the closure returns.
+        unsafe {
+            Gc::with_unique(root.wide, mt, |wide| {
+                for (field, value) in wide.fields.iter_mut().zip(values) {
+                    *field = Some(value);
+                    wide.count += 1;
+                }
+            })
+        };
+    });
+
+    // A full collection would restart marking, so finish the current cycle instead.
+    while a.collect_generation_stats().completed_cycles == 0 {
+        a.run_collection();
+    }
+    assert_eq!(a.allocations(), 10);
+
+    a.view(|root, _| {
+        assert_eq!(root.wide.count, 8);
+        assert!(root.wide.fields.iter().map(|f| *f.unwrap()).eq(0..8));
+    });
+}
+
+#[test]
+fn unreachable_objects_lists_detached() {
+    let mut a = Arena::<HalfRoot>::new(|mt| vec![Gc::new(1, mt), Gc::new(2, mt)]);
+
+    assert!(a.unreachable_objects().is_empty());
+
+    let detached = a.view(|_, mt| {
+        let detached = Gc::new(String::from("detached"), mt);
+        detached.as_ptr().cast_const().cast::<()>()
+    });
+
+    assert_eq!(
+        a.unreachable_objects(),
+        vec![(detached, core::any::type_name::<String>())]
+    );
+    assert_eq!(a.allocations(), 3);
+
+    a.complete_collection();
+    assert_eq!(a.allocations(), 2);
+    assert!(a.unreachable_objects().is_empty());
+}
+
+#[test]
+fn reset_cycle_stats_windows_measurement() {
+    let mut a = Arena::<HalfRoot>::new(|_| Vec::new());
+
+    // A warm up which shouldn't be measured.
+    for _ in 0..3 {
+        a.view(|_, mt| {
+            let _garbage = Gc::new(0, mt);
+        });
+        a.complete_collection();
+    }
+    assert_eq!(a.collect_generation_stats().completed_cycles, 3);
+
+    a.reset_cycle_stats();
+    assert_eq!(a.collect_generation_stats(), GenerationStats::default());
+
+    for i in 0..2 {
+        a.view_mut(|root, mt| {
+            root.push(Gc::new(i, mt));
+            for _ in 0..3 {
+                let _garbage = Gc::new(0, mt);
+            }
+        });
+        a.complete_collection();
+    }
+
+    let stats = a.collect_generation_stats();
+    assert_eq!(stats.completed_cycles, 2);
+    assert_eq!(stats.freed, 6);
+    // One survivor in the first cycle, and two in the second.
+    assert_eq!(stats.survived, 3);
+}
+
+#[test]
+fn order_key_stable_across_collections() {
+    let mut a = Arena::<HalfRoot>::new(|mt| (0..8).map(|i| Gc::new(i, mt)).collect());
+    let keys = a.view(|root, _| root.iter().map(|gc| gc.order_key()).collect::<Vec<_>>());
+
+    let mut unique = keys.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), keys.len());
+
+    for _ in 0..3 {
+        a.view(|_, mt| {
+            for i in 0..100 {
+                let _garbage = Gc::new(i, mt);
+            }
+        });
+        a.complete_collection();
+        let after = a.view(|root, _| root.iter().map(|gc| gc.order_key()).collect::<Vec<_>>());
+        assert_eq!(after, keys);
+    }
+}
+
+#[test]
+fn unstored_allocations_survive_view_mut() {
+    let pacing = Pacing {
+        trigger_allocations: Some(0),
+        mark_stride: 1,
+        ..Pacing::default()
+    };
+    let mut a = Arena::<HalfRoot>::new_paced(|mt| vec![Gc::new(0, mt)], pacing);
+
+    // Leave a collection in progress, so the closure runs between two steps.
+    a.run_collection();
+    a.run_collection();
+    assert!(a.needs_collection());
+
+    a.view_mut(|root, mt| {
+        let pending: Vec<_> = (1..1000).map(|i| Gc::new(i, mt)).collect();
+        assert!(pending.iter().zip(1..).all(|(gc, i)| **gc == i));
+        root.extend(pending);
+    });
+
+    a.complete_collection();
+    a.view(|root, _| assert!(root.iter().map(|gc| **gc).eq(0..1000)));
+    assert_eq!(a.allocations(), 1000);
+}
+
+#[test]
+fn addresses_stable_across_collections() {
+    let pacing = Pacing {
+        trigger_allocations: Some(0),
+        mark_stride: 1,
+        sweep_stride: 1,
+        ..Pacing::default()
+    };
+    let mut a =
+        Arena::<HalfRoot>::new_paced(|mt| (0..16).map(|i| Gc::new(i, mt)).collect(), pacing);
+    assert!(!a.is_compacting());
+
+    let addresses = |a: &Arena<HalfRoot>| {
+        a.view(|root, _| root.iter().map(|gc| gc.as_ptr().addr()).collect::<Vec<_>>())
+    };
+    let before = addresses(&a);
+
+    for round in 0..50 {
+        // Unroot the objects from the previous round and allocate garbage, so that the sweep
+        // leaves holes between the survivors.
+        a.view_mut(|root, mt| {
+            root.truncate(16);
+            root.extend((0..8).map(|i| Gc::new(round * 8 + i, mt)));
+            for _ in 0..8 {
+                let _garbage = Gc::new(0, mt);
+            }
+        });
+        a.run_collection();
+        assert_eq!(addresses(&a)[..16], before);
+    }
+
+    a.complete_collection();
+    assert_eq!(addresses(&a)[..16], before);
+    a.view(|root, _| assert!(root[..16].iter().map(|gc| **gc).eq(0..16)));
+}
+
+#[test]
+fn allocation_pressure_tracks_trigger() {
+    let pacing = Pacing {
+        trigger_allocations: Some(10),
+        trigger_bytes: None,
+        ..Pacing::default()
+    };
+    let mut a = Arena::<HalfRoot>::new_paced(|_| Vec::new(), pacing);
+    assert_eq!(a.view(|_, mt| mt.allocation_pressure()), 0.0);
+
+    let mut last = 0.0;
+    for i in 1..=10 {
+        let pressure = a.view_mut(|root, mt| {
+            root.push(Gc::new(i, mt));
+            mt.allocation_pressure()
+        });
+        assert!(pressure > last);
+        last = pressure;
+    }
+    assert_eq!(last, 1.0);
+    assert!(a.needs_collection());
+
+    a.complete_collection();
+    assert_eq!(a.view(|_, mt| mt.allocation_pressure()), 0.0);
+
+    // Only the closest trigger counts.
+    let pacing = Pacing {
+        trigger_allocations: Some(100),
+        trigger_bytes: Some(64),
+        ..Pacing::default()
+    };
+    let a = Arena::<HalfRoot>::new_paced(|mt| vec![Gc::new(0, mt)], pacing);
+    assert!(a.view(|_, mt| mt.allocation_pressure()) > 0.1);
+}
+
+#[test]
+fn pinned_object_survives_until_unpinned() {
+    let mut a = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+
+    let (pin, address) = a.view(|_, mt| {
+        let gc = Gc::new(7u32, mt);
+        let _garbage = Gc::new(0u32, mt);
+        (mt.pin(gc), gc.as_ptr().cast_const())
+    });
+    assert_eq!(pin.as_ptr(), address);
+
+    for _ in 0..3 {
+        a.complete_collection();
+        assert_eq!(a.allocations(), 1);
+        // Safety: The pin keeps the object alive.
+        assert_eq!(unsafe { *pin.as_ptr() }, 7);
+    }
+
+    drop(pin);
+    a.complete_collection();
+    assert_eq!(a.allocations(), 0);
+}
+
+struct SharedRoot;
+
+impl Rootable for SharedRoot {
+    type Root<'l> = Gc<'l, Gc<'l, LockedRefCell<Vec<Gc<'l, u32>>>>>;
+}
+
+#[test]
+fn pin_during_mark_after_root_traced() {
+    let pacing = Pacing {
+        trigger_allocations: Some(0),
+        mark_stride: 1,
+        ..Pacing::default()
+    };
+    let mut a = Arena::<SharedRoot>::new_paced(
+        |mt| {
+            let list = LockedRefCell::new(vec![Gc::new(1, mt), Gc::new(2, mt)]);
+            Gc::new(Gc::new(list, mt), mt)
+        },
+        pacing,
+    );
+
+    // Start marking, and trace the root and the object it points to, but not yet the list.
+    a.run_collection();
+    a.run_collection();
+
+    // The object is only reachable from the pin once it is removed from the list.
+    let pin = a.view(|root, mt| {
+        let removed = root.unlock().borrow_mut().pop().unwrap();
+        mt.pin(removed)
+    });
+    // Finish the cycle in progress, rather than starting a new one.
+    while a.collect_generation_stats().completed_cycles == 0 {
+        a.run_collection();
+    }
+    assert_eq!(a.allocations(), 4);
+    // Safety: The pin keeps the object alive.
+    assert_eq!(unsafe { *pin.as_ptr() }, 2);
+}
+
+#[test]
+fn manual_pacing_only_collects_explicitly() {
+    let mut a = Arena::<HalfRoot>::new_paced(|_| Vec::new(), Pacing::MANUAL);
+
+    for _ in 0..10 {
+        a.view(|_, mt| {
+            for i in 0..100 {
+                let _garbage = Gc::new(i, mt);
+            }
+        });
+        assert!(!a.needs_collection());
+        a.run_collection();
+    }
+    assert_eq!(a.allocations(), 1000);
+
+    a.complete_collection();
+    assert_eq!(a.allocations(), 0);
+}
+
+#[test]
+fn manual_pacing_ignores_triggers() {
+    let pacing = Pacing {
+        manual: true,
+        ..Pacing::default()
+    };
+    let mut a = Arena::<HalfRoot>::new_paced(|_| Vec::new(), pacing);
+
+    a.view(|_, mt| {
+        for i in 0..1000 {
+            let _garbage = Gc::new(i, mt);
+        }
+    });
+    assert!(!a.needs_collection());
+    a.run_collection();
+    assert_eq!(a.allocations(), 1000);
+}
+
+#[test]
+#[should_panic = "use `Pacing::MANUAL`"]
+fn pacing_without_triggers_is_rejected() {
+    let pacing = Pacing {
+        trigger_bytes: None,
+        trigger_allocations: None,
+        ..Pacing::default()
+    };
+    let _a = Arena::<HalfRoot>::new_paced(|_| Vec::new(), pacing);
+}
+
+#[test]
+fn collection_report_counts_garbage() {
+    let mut a = Arena::<HalfRoot>::new(|mt| vec![Gc::new(0, mt)]);
+    let kept = a.live_bytes();
+
+    a.view(|_, mt| {
+        for i in 0..10 {
+            let _garbage = Gc::new(i, mt);
+        }
+        let _large = Gc::new([0u64; 32], mt);
+    });
+    let garbage = a.live_bytes() - kept;
+    assert!(garbage > 11 * 4 + 256);
+
+    let report = a.complete_collection_reporting();
+    assert_eq!(report.objects_freed, 11);
+    assert_eq!(report.bytes_freed, garbage);
+    assert_eq!(report.objects_surviving, 1);
+    assert_eq!(a.live_bytes(), kept);
+
+    let report = a.complete_collection_reporting();
+    assert_eq!((report.objects_freed, report.bytes_freed), (0, 0));
+}
+
+struct Halves<'b> {
+    head: Gc<'b, [u32]>,
+    tail: Gc<'b, [u32]>,
+}
+
+unsafe impl Collect for Halves<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.head.trace(c);
+        self.tail.trace(c);
+    }
+}
+
+impl Rootable for Halves<'static> {
+    type Root<'l> = Halves<'l>;
+}
+
+#[test]
+fn split_off_slice() {
+    let mut a = Arena::<Halves>::new(|mt| {
+        let mut head = UniqueGc::from_slice(&[1, 2, 3, 4, 5], mt);
+        let tail = head.split_off(2, mt);
+
+        Halves {
+            head: UniqueGc::into_gc(head),
+            tail: UniqueGc::into_gc(tail),
+        }
+    });
+
+    a.complete_collection();
+    assert_eq!(a.allocations(), 2);
+
+    a.view(|root, mt| {
+        assert_eq!(*root.head, [1, 2]);
+        assert_eq!(*root.tail, [3, 4, 5]);
+
+        // Splitting at either end leaves one of the halves empty.
+        let mut copy = UniqueGc::from_slice(&root.tail, mt);
+        assert!(copy.split_off(3, mt).is_empty());
+        assert_eq!(*copy.split_off(0, mt), [3, 4, 5]);
+        assert!(copy.is_empty());
+    });
+}
+
+struct ListRoot;
+
+impl Rootable for ListRoot {
+    type Root<'l> = Gc<'l, LockedRefCell<Vec<Gc<'l, u32>>>>;
+}
+
+#[test]
+fn rollback_frees_speculative_allocations() {
+    let mut a = Arena::<ListRoot>::new(|mt| {
+        let list = vec![Gc::new(1, mt), Gc::new(2, mt)];
+        Gc::new(LockedRefCell::new(list), mt)
+    });
+    let checkpoint = a.checkpoint();
+
+    a.view_mut(|root, mt| {
+        // Speculatively replace the root, without touching any object which already exists.
+        let list = root
+            .borrow()
+            .iter()
+            .map(|n| Gc::new(**n * 10, mt))
+            .collect();
+        *root = Gc::new(LockedRefCell::new(list), mt);
+    });
+    assert_eq!(a.allocations(), 6);
+
+    a.rollback(checkpoint);
+    assert_eq!(a.allocations(), 3);
+    a.view(|root, _| {
+        let values: Vec<u32> = root.borrow().iter().map(|n| **n).collect();
+        assert_eq!(values, [1, 2]);
+    });
+}
+
+#[test]
+#[should_panic = "allocated since the checkpoint is still reachable"]
+fn rollback_refuses_reachable_allocations() {
+    let mut a = Arena::<ListRoot>::new(|mt| Gc::new(LockedRefCell::new(Vec::new()), mt));
+    let checkpoint = a.checkpoint();
+
+    // The list existed at the checkpoint, so the new object would be left dangling.
+    a.view(|root, mt| root.unlock().borrow_mut().push(Gc::new(1, mt)));
+    a.rollback(checkpoint);
+}
+
+#[test]
+fn alloc_batch_from_allocating_iterator() {
+    let mut a = Arena::<HalfRoot>::new(|mt| {
+        // The iterator allocates too, which the batch mustn't conflict with.
+        let boxed = mt.alloc_batch((0..100).map(|i| Gc::new(i, mt)));
+        boxed.iter().map(|gc| **gc).collect()
+    });
+    assert_eq!(a.allocations(), 200);
+
+    a.complete_collection();
+    assert_eq!(a.allocations(), 100);
+    a.view(|root, _| assert!(root.iter().map(|gc| **gc).eq(0..100)));
+}
+
+#[test]
+fn run_steps_keeps_heap_bounded() {
+    let mut a = Arena::<HalfRoot>::new(|_| Vec::new());
+
+    for _ in 0..10 {
+        a.run_steps(100, |stack, mt| {
+            // Each step replaces the top of the stack, leaving the old value as garbage.
+            let top = stack.pop().map_or(0, |top| *top);
+            stack.push(Gc::new(top + 1, mt));
+            for i in 0..4 {
+                let _temporary = Gc::new(i, mt);
+            }
+        });
+
+        // Of the 5000 objects allocated in total, only those of about one cycle remain.
+        let live = a.allocations();
+        assert!(live < 150, "{live} objects allocated");
+    }
+
+    a.view(|stack, _| assert_eq!(*stack[0], 1000));
+}
+
+#[test]
+fn collect_until_stops_between_steps() {
+    let pacing = Pacing {
+        trigger_allocations: Some(0),
+        mark_stride: 1,
+        ..Pacing::default()
+    };
+    let mut a =
+        Arena::<HalfRoot>::new_paced(|mt| (0..10).map(|i| Gc::new(i, mt)).collect(), pacing);
+    a.view(|_, mt| {
+        for i in 0..5 {
+            let _garbage = Gc::new(i, mt);
+        }
+    });
+
+    let mut steps = 0;
+    let finished = a.collect_until(|| {
+        steps += 1;
+        steps > 3
+    });
+
+    // Three steps wake the collector and mark two objects, without freeing anything.
+    assert!(!finished);
+    assert_eq!(steps, 4);
+    assert!(matches!(
+        a.save_collection_cursor().phase,
+        CursorPhase::Mark { .. }
+    ));
+    assert_eq!(a.allocations(), 15);
+
+    assert!(a.collect_until(|| false));
+    assert_eq!(a.save_collection_cursor().phase, CursorPhase::Sleep);
+    assert_eq!(a.allocations(), 10);
+}
+
+/// A future which is pending the first time it is polled.
+struct YieldNow(bool);
+
+impl std::future::Future for YieldNow {
+    type Output = ();
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<()> {
+        if self.0 {
+            std::task::Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            std::task::Poll::Pending
+        }
+    }
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+
+    loop {
+        if let std::task::Poll::Ready(out) = future.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+#[test]
+fn view_async_allocates_across_await() {
+    let mut a = Arena::<HalfRoot>::new(|mt| vec![Gc::new(10, mt)]);
+
+    let sum = block_on(a.view_async(async |stack, mt| {
+        let before = Gc::new(1, mt);
+        YieldNow(false).await;
+        let after = Gc::new(2, mt);
+
+        *stack[0] + *before + *after
+    }));
+    assert_eq!(sum, 13);
+    assert_eq!(a.allocations(), 3);
+
+    a.complete_collection();
+    assert_eq!(a.allocations(), 1);
+}
+
+thread_local! {
+    static SYMBOLS: ThreadLocalRoots<str> = const { ThreadLocalRoots::new() };
+}
+
+/// Interns a symbol from deep within a call stack, without returning it to the caller.
+fn intern_deep<'b>(depth: usize, name: &'static str, mt: &Mutation<'b>) {
+    if depth == 0 {
+        SYMBOLS.with(|symbols| symbols.insert(name, Gc::from_str(name, mt), mt));
+    } else {
+        intern_deep(depth - 1, name, mt);
+    }
+}
+
+#[test]
+fn thread_local_roots_survive_collection() {
+    let mut a = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+    let other = Arena::<EmptyRoot>::new(|_| EmptyRoot);
+
+    a.view(|_, mt| {
+        intern_deep(50, "lambda", mt);
+        intern_deep(50, "define", mt);
+    });
+    a.complete_collection();
+    assert_eq!(a.allocations(), 2);
+
+    a.view(|_, mt| {
+        let lambda = SYMBOLS.with(|symbols| symbols.get("lambda", mt)).unwrap();
+        assert_eq!(&*lambda, "lambda");
+        assert!(SYMBOLS.with(|symbols| symbols.remove("define", mt)));
+    });
+    // Symbols are only visible to the arena which allocated them.
+    other.view(|_, mt| assert!(SYMBOLS.with(|symbols| symbols.get("lambda", mt)).is_none()));
+
+    a.complete_collection();
+    assert_eq!(a.allocations(), 1);
+}
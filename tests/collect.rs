@@ -0,0 +1,687 @@
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use ghost_gc::{
+    once_arena, Arena, Collect, Collector, External, Gc, GcSlice, PartialSlice, Rootable,
+    TransparentCollect, UniqueGc,
+};
+
+thread_local! {
+    static LEAF_TRACES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A leaf type which counts how many times it has been traced.
+struct CountedLeaf;
+
+unsafe impl Collect for CountedLeaf {
+    const NEEDS_TRACE: bool = false;
+
+    fn trace(&self, _c: &Collector) {
+        LEAF_TRACES.set(LEAF_TRACES.get() + 1);
+    }
+}
+
+/// A root which holds a single `'static` value.
+struct StaticRoot<T>(T);
+
+unsafe impl<T: Collect> Collect for StaticRoot<T> {
+    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+
+    fn trace(&self, c: &Collector) {
+        self.0.trace(c);
+    }
+}
+
+impl<T: Collect + 'static> Rootable for StaticRoot<T> {
+    type Root<'l> = StaticRoot<T>;
+}
+
+struct Mixed<'b> {
+    external: std::rc::Weak<RefCell<u32>>,
+    value: Gc<'b, u32>,
+}
+
+unsafe impl Collect for Mixed<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.external.trace(c);
+        self.value.trace(c);
+    }
+}
+
+#[test]
+fn std_weak_beside_gc() {
+    let shared = Rc::new(RefCell::new(7));
+
+    once_arena(|mt| {
+        let mixed = Gc::new(
+            Mixed {
+                external: Rc::downgrade(&shared),
+                value: Gc::new(3, mt),
+            },
+            mt,
+        );
+
+        assert_eq!(*mixed.external.upgrade().unwrap().borrow(), 7);
+        assert_eq!(*mixed.value, 3);
+    });
+}
+
+#[test]
+fn leaf_vec_is_not_iterated() {
+    LEAF_TRACES.set(0);
+
+    let mut a = Arena::<StaticRoot<Vec<CountedLeaf>>>::new(|_| {
+        StaticRoot((0..1_000_000).map(|_| CountedLeaf).collect())
+    });
+
+    a.complete_collection();
+
+    assert_eq!(LEAF_TRACES.get(), 0);
+}
+
+#[test]
+fn leaf_collections_are_not_iterated() {
+    use std::collections::{LinkedList, VecDeque};
+
+    LEAF_TRACES.set(0);
+
+    let mut a = Arena::<StaticRoot<VecDeque<CountedLeaf>>>::new(|_| {
+        StaticRoot((0..1_000_000).map(|_| CountedLeaf).collect())
+    });
+    a.complete_collection();
+
+    let mut a = Arena::<StaticRoot<LinkedList<CountedLeaf>>>::new(|_| {
+        StaticRoot((0..1_000).map(|_| CountedLeaf).collect())
+    });
+    a.complete_collection();
+
+    let mut a = Arena::<StaticRoot<(Option<CountedLeaf>, [CountedLeaf; 64])>>::new(|_| {
+        StaticRoot((Some(CountedLeaf), [const { CountedLeaf }; 64]))
+    });
+    a.complete_collection();
+
+    assert_eq!(LEAF_TRACES.get(), 0);
+}
+
+struct FfiName<'b> {
+    name: std::ffi::CString,
+    path: std::path::PathBuf,
+    value: Gc<'b, u32>,
+}
+
+unsafe impl Collect for FfiName<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.name.trace(c);
+        self.path.trace(c);
+        self.value.trace(c);
+    }
+}
+
+#[test]
+fn ffi_strings_beside_gc() {
+    once_arena(|mt| {
+        let name = Gc::new(
+            FfiName {
+                name: std::ffi::CString::new("symbol").unwrap(),
+                path: std::path::PathBuf::from("/usr/lib"),
+                value: Gc::new(1, mt),
+            },
+            mt,
+        );
+
+        assert_eq!(name.name.as_bytes(), b"symbol");
+        assert_eq!(*name.value, 1);
+
+        let _: Gc<std::ffi::OsString> = Gc::new(std::ffi::OsString::from("os"), mt);
+    });
+}
+
+struct ExternalBuffer<'b> {
+    data: std::ptr::NonNull<u8>,
+    len: usize,
+    owner: Gc<'b, u32>,
+}
+
+unsafe impl Collect for ExternalBuffer<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.data.trace(c);
+        self.len.trace(c);
+        self.owner.trace(c);
+    }
+}
+
+#[test]
+fn non_null_beside_gc() {
+    static BYTES: [u8; 4] = [1, 2, 3, 4];
+
+    once_arena(|mt| {
+        let buf = Gc::new(
+            ExternalBuffer {
+                data: std::ptr::NonNull::from(&BYTES).cast(),
+                len: BYTES.len(),
+                owner: Gc::new(0, mt),
+            },
+            mt,
+        );
+
+        let bytes = unsafe { std::slice::from_raw_parts(buf.data.as_ptr(), buf.len) };
+        assert_eq!(bytes, [1, 2, 3, 4]);
+        assert_eq!(*buf.owner, 0);
+    });
+}
+
+thread_local! {
+    static NODE_TRACES: Cell<usize> = const { Cell::new(0) };
+}
+
+struct Node(u32);
+
+unsafe impl Collect for Node {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, _c: &Collector) {
+        NODE_TRACES.set(NODE_TRACES.get() + 1);
+    }
+}
+
+struct PartialRoot;
+
+impl Rootable for PartialRoot {
+    type Root<'l> = PartialSlice<'l, Gc<'l, Node>>;
+}
+
+/// Should also be run under Miri, which catches any read of an uninitialized element.
+#[test]
+fn partial_slice_traces_initialized_prefix() {
+    NODE_TRACES.set(0);
+
+    let mut arena = Arena::<PartialRoot>::new(|mt| PartialSlice::new(6, mt));
+
+    arena.view_mut(|slice, mt| {
+        for i in 0..3 {
+            assert!(slice.push(Gc::new(Node(i), mt)).is_ok());
+        }
+    });
+
+    arena.complete_collection();
+    assert_eq!(NODE_TRACES.get(), 3);
+    assert_eq!(arena.allocations(), 4);
+
+    arena.view_mut(|slice, mt| {
+        for i in 3..6 {
+            assert!(slice.push(Gc::new(Node(i), mt)).is_ok());
+        }
+        assert!(slice.push(Gc::new(Node(6), mt)).is_err());
+    });
+
+    NODE_TRACES.set(0);
+    arena.complete_collection();
+    assert_eq!(NODE_TRACES.get(), 6);
+    assert_eq!(arena.allocations(), 7);
+
+    arena.view(|slice, _| {
+        let values: Vec<_> = slice.as_slice().iter().map(|node| node.0).collect();
+        assert_eq!(values, [0, 1, 2, 3, 4, 5]);
+    });
+}
+
+struct SharedRoot;
+
+impl Rootable for SharedRoot {
+    type Root<'l> = PartialSlice<'l, Rc<()>>;
+}
+
+/// Should also be run under Miri, which catches a drop of an uninitialized element.
+#[test]
+fn partial_slice_drops_initialized_prefix() {
+    let shared = Rc::new(());
+
+    let arena = Arena::<SharedRoot>::new(|mt| {
+        let mut slice = PartialSlice::new(4, mt);
+        for _ in 0..2 {
+            assert!(slice.push(Rc::clone(&shared)).is_ok());
+        }
+        slice
+    });
+    assert_eq!(Rc::strong_count(&shared), 3);
+
+    drop(arena);
+    assert_eq!(Rc::strong_count(&shared), 1);
+}
+
+struct BuilderRoot;
+
+impl Rootable for BuilderRoot {
+    type Root<'l> = Option<PartialSlice<'l, Gc<'l, u32>>>;
+}
+
+#[test]
+fn slice_builder_across_collections() {
+    let mut arena = Arena::<BuilderRoot>::new(|mt| Some(mt.slice_builder(1000)));
+
+    for chunk in 0..10 {
+        arena.view_mut(|root, mt| {
+            let builder = root.as_mut().unwrap();
+            for i in 0..100 {
+                let _garbage = Gc::new(0u32, mt);
+                assert!(builder.push(Gc::new(chunk * 100 + i, mt)).is_ok());
+            }
+        });
+
+        arena.run_collection();
+        arena.run_collection();
+    }
+
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 1001);
+
+    arena.view_mut(|root, _| {
+        let slice = root.take().unwrap().into_gc().unwrap();
+        assert_eq!(slice.len(), 1000);
+        assert!(slice.iter().enumerate().all(|(i, v)| **v == i as u32));
+    });
+
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 0);
+}
+
+/// A trivial smart pointer, which is traced through its target.
+struct SmartPtr<T>(Box<T>);
+
+impl<T> std::ops::Deref for SmartPtr<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+unsafe impl<T> TransparentCollect for SmartPtr<T> {}
+
+struct SmartRoot;
+
+impl Rootable for SmartRoot {
+    type Root<'l> = SmartPtr<Gc<'l, SmartPtr<Gc<'l, u32>>>>;
+}
+
+#[test]
+fn transparent_smart_pointer() {
+    const { assert!(<SmartPtr<Gc<u32>> as Collect>::NEEDS_TRACE) };
+    const { assert!(!<SmartPtr<u32> as Collect>::NEEDS_TRACE) };
+
+    let mut arena = Arena::<SmartRoot>::new(|mt| {
+        let _garbage = Gc::new(0u32, mt);
+        SmartPtr(Box::new(Gc::new(SmartPtr(Box::new(Gc::new(5, mt))), mt)))
+    });
+
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 2);
+    arena.view(|root, _| assert_eq!(*****root, 5));
+}
+
+struct NestedRoot;
+
+impl Rootable for NestedRoot {
+    type Root<'l> = Vec<Option<Result<Gc<'l, u32>, String>>>;
+}
+
+#[test]
+fn nested_generics_trace_inner_gc() {
+    const { assert!(<Vec<Option<Result<Gc<u32>, String>>> as Collect>::NEEDS_TRACE) };
+    const { assert!(!<Vec<Option<Result<u32, String>>> as Collect>::NEEDS_TRACE) };
+
+    let mut arena = Arena::<NestedRoot>::new(|mt| {
+        (0..30)
+            .map(|i| match i % 3 {
+                0 => None,
+                1 => Some(Err(format!("error {i}"))),
+                _ => {
+                    let _garbage = Gc::new(0u32, mt);
+                    Some(Ok(Gc::new(i, mt)))
+                }
+            })
+            .collect()
+    });
+    assert_eq!(arena.allocations(), 20);
+
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 10);
+
+    arena.view(|root, _| {
+        let live: Vec<u32> = root.iter().flatten().flatten().map(|gc| **gc).collect();
+        assert_eq!(live, (0..30).filter(|i| i % 3 == 2).collect::<Vec<_>>());
+    });
+}
+
+struct SubSliceRoot;
+
+impl Rootable for SubSliceRoot {
+    type Root<'l> = GcSlice<'l, u8>;
+}
+
+#[test]
+fn sub_slice_keeps_backing_alive() {
+    let mut arena = Arena::<SubSliceRoot>::new(|mt| {
+        let bytes = UniqueGc::into_gc(UniqueGc::from_slice(b"GET /index.html HTTP/1.1", mt));
+        GcSlice::new(bytes).slice(4..15)
+    });
+
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 1);
+
+    arena.view(|path, _| {
+        assert_eq!(&**path, b"/index.html");
+        assert_eq!(path.range(), 4..15);
+        assert_eq!(path.backing().len(), 24);
+
+        let name = path.slice(1..6);
+        assert_eq!(&*name, b"index");
+        assert_eq!(name.range(), 5..10);
+        assert!(path.slice(11..11).is_empty());
+    });
+}
+
+#[test]
+#[should_panic = "range end index 4 out of range for slice of length 3"]
+fn sub_slice_out_of_bounds() {
+    once_arena(|mt| {
+        let bytes = UniqueGc::into_gc(UniqueGc::from_slice(b"abcdef", mt));
+        let _ = GcSlice::new(bytes).slice(1..4).slice(0..4);
+    });
+}
+
+struct UnsizedRoot;
+
+impl Rootable for UnsizedRoot {
+    type Root<'l> = Gc<'l, [Gc<'l, u32>]>;
+}
+
+#[test]
+fn unsized_array_traces_elements() {
+    let mut arena = Arena::<UnsizedRoot>::new(|mt| {
+        let array = UniqueGc::new_unsizable([0, 1, 2, 3].map(|i| Gc::new(i, mt)), mt);
+        UniqueGc::into_gc(array).unsize(mt)
+    });
+
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 5);
+    arena.view(|slice, _| assert!(slice.iter().map(|gc| **gc).eq(0..4)));
+}
+
+/// A node which shares a buffer with code outside of the arena.
+struct Buffered<'b> {
+    buffer: External<Rc<RefCell<Vec<u8>>>>,
+    next: Option<Gc<'b, Buffered<'b>>>,
+}
+
+unsafe impl Collect for Buffered<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.buffer.trace(c);
+        self.next.trace(c);
+    }
+}
+
+struct BufferedRoot;
+
+impl Rootable for BufferedRoot {
+    type Root<'l> = Gc<'l, Buffered<'l>>;
+}
+
+#[test]
+fn external_shared_buffer() {
+    const { assert!(!<External<Rc<RefCell<Vec<u8>>>> as Collect>::NEEDS_TRACE) };
+
+    let shared = Rc::new(RefCell::new(vec![1, 2]));
+    let mut arena = Arena::<BufferedRoot>::new(|mt| {
+        let tail = Gc::new(
+            Buffered {
+                buffer: External::new(Rc::new(RefCell::new(Vec::new()))),
+                next: None,
+            },
+            mt,
+        );
+        Gc::new(
+            Buffered {
+                buffer: External::new(shared.clone()),
+                next: Some(tail),
+            },
+            mt,
+        )
+    });
+
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 2);
+
+    shared.borrow_mut().push(3);
+    arena.view(|root, _| {
+        assert_eq!(*root.buffer.borrow(), [1, 2, 3]);
+        root.buffer.borrow_mut().push(4);
+        assert!(root.next.unwrap().buffer.borrow().is_empty());
+    });
+    assert_eq!(*shared.borrow(), [1, 2, 3, 4]);
+
+    drop(arena);
+    assert_eq!(Rc::strong_count(&shared), 1);
+}
+
+#[cfg(feature = "tinyvec")]
+struct OperandRoot;
+
+#[cfg(feature = "tinyvec")]
+impl Rootable for OperandRoot {
+    type Root<'l> = tinyvec::ArrayVec<[Option<Gc<'l, Node>>; 8]>;
+}
+
+#[cfg(feature = "tinyvec")]
+#[test]
+fn inline_vec_traces_spare_capacity() {
+    NODE_TRACES.set(0);
+
+    // Only the first three slots are in use, but the rest of the backing array can still be
+    // reached, through `into_inner`.
+    let mut arena = Arena::<OperandRoot>::new(|mt| {
+        let backing = std::array::from_fn(|i| Some(Gc::new(Node(i as u32), mt)));
+        tinyvec::ArrayVec::from_array_len(backing, 3)
+    });
+
+    arena.complete_collection();
+    assert_eq!(NODE_TRACES.get(), 8);
+    assert_eq!(arena.allocations(), 8);
+
+    arena.view(|stack, _| {
+        assert_eq!(stack.len(), 3);
+        let values: Vec<_> = stack
+            .into_inner()
+            .iter()
+            .map(|node| node.unwrap().0)
+            .collect();
+        assert_eq!(values, (0..8).collect::<Vec<_>>());
+    });
+}
+
+#[cfg(feature = "arrayvec")]
+struct StackRoot;
+
+#[cfg(feature = "arrayvec")]
+impl Rootable for StackRoot {
+    type Root<'l> = arrayvec::ArrayVec<Gc<'l, Node>, 8>;
+}
+
+#[cfg(feature = "arrayvec")]
+#[test]
+fn array_vec_partially_filled() {
+    NODE_TRACES.set(0);
+
+    let mut arena = Arena::<StackRoot>::new(|mt| (0..3).map(|i| Gc::new(Node(i), mt)).collect());
+
+    arena.view(|_, mt| {
+        let _garbage = Gc::new(Node(3), mt);
+    });
+    arena.complete_collection();
+    assert_eq!(NODE_TRACES.get(), 3);
+    assert_eq!(arena.allocations(), 3);
+
+    arena.view_mut(|stack, _| assert_eq!(stack.pop().map(|node| node.0), Some(2)));
+    NODE_TRACES.set(0);
+    arena.complete_collection();
+    assert_eq!(NODE_TRACES.get(), 2);
+    assert_eq!(arena.allocations(), 2);
+}
+
+#[cfg(feature = "triomphe")]
+struct Symbol<'b> {
+    name: triomphe::Arc<str>,
+    value: Gc<'b, u32>,
+}
+
+#[cfg(feature = "triomphe")]
+unsafe impl Collect for Symbol<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.name.trace(c);
+        self.value.trace(c);
+    }
+}
+
+#[cfg(feature = "triomphe")]
+#[test]
+fn triomphe_arc_beside_gc() {
+    let name = triomphe::Arc::<str>::from("answer");
+
+    once_arena(|mt| {
+        let symbol = Gc::new(
+            Symbol {
+                name: name.clone(),
+                value: Gc::new(42, mt),
+            },
+            mt,
+        );
+
+        assert_eq!(&*symbol.name, "answer");
+        assert_eq!(*symbol.value, 42);
+    });
+}
+
+struct Peer<'b> {
+    address: std::net::SocketAddr,
+    sent: std::num::Saturating<u32>,
+    name: Gc<'b, str>,
+}
+
+unsafe impl Collect for Peer<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.address.trace(c);
+        self.sent.trace(c);
+        self.name.trace(c);
+    }
+}
+
+#[test]
+fn socket_addr_beside_gc() {
+    once_arena(|mt| {
+        let peer = Gc::new(
+            Peer {
+                address: "127.0.0.1:8080".parse().unwrap(),
+                sent: std::num::Saturating(u32::MAX),
+                name: Gc::from_str("local", mt),
+            },
+            mt,
+        );
+
+        assert_eq!(peer.address.port(), 8080);
+        assert_eq!((peer.sent + std::num::Saturating(1)).0, u32::MAX);
+        assert_eq!(&*peer.name, "local");
+    });
+}
+
+enum Expr<'b> {
+    Lit(i64),
+    Var(Gc<'b, str>),
+    Add(Box<Expr<'b>>, Box<Expr<'b>>),
+}
+
+// Only the non-recursive fields decide `NEEDS_TRACE`, as referring to `Box<Self>` would be a
+// cycle.
+unsafe impl Collect for Expr<'_> {
+    const NEEDS_TRACE: bool = <i64 as Collect>::NEEDS_TRACE || <Gc<str> as Collect>::NEEDS_TRACE;
+
+    fn trace(&self, c: &Collector) {
+        match self {
+            Expr::Lit(_) => {}
+            Expr::Var(name) => name.trace(c),
+            Expr::Add(lhs, rhs) => {
+                lhs.trace(c);
+                rhs.trace(c);
+            }
+        }
+    }
+}
+
+struct ExprRoot;
+
+impl Rootable for ExprRoot {
+    type Root<'l> = Expr<'l>;
+}
+
+#[test]
+fn recursive_boxed_expr() {
+    const { assert!(<Expr as Collect>::NEEDS_TRACE) };
+
+    let mut arena = Arena::<ExprRoot>::new(|mt| {
+        let _garbage = Gc::from_str("unused", mt);
+        let var = |name| Box::new(Expr::Var(Gc::from_str(name, mt)));
+        let lhs = Box::new(Expr::Add(var("x"), Box::new(Expr::Lit(1))));
+        Expr::Add(lhs, var("y"))
+    });
+
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 2);
+    arena.view(|expr, _| assert_eq!(render(expr), "((x + 1) + y)"));
+}
+
+fn render(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(value) => value.to_string(),
+        Expr::Var(name) => name.to_string(),
+        Expr::Add(lhs, rhs) => format!("({} + {})", render(lhs), render(rhs)),
+    }
+}
+
+struct CowRoot;
+
+impl Rootable for CowRoot {
+    type Root<'l> = (Cow<'l, [Gc<'l, u32>]>, Cow<'static, str>);
+}
+
+#[test]
+fn cow_traces_owned_values() {
+    const { assert!(<Cow<[Gc<u32>]> as Collect>::NEEDS_TRACE) };
+    const { assert!(!<Cow<str> as Collect>::NEEDS_TRACE) };
+
+    let mut arena = Arena::<CowRoot>::new(|mt| {
+        let _garbage = Gc::new(0u32, mt);
+        let values = (1..4).map(|i| Gc::new(i, mt)).collect::<Vec<_>>();
+        (Cow::Owned(values), Cow::Borrowed("name"))
+    });
+
+    arena.complete_collection();
+    assert_eq!(arena.allocations(), 3);
+    arena.view(|(values, name), _| {
+        assert!(values.iter().map(|gc| **gc).eq(1..4));
+        assert_eq!(name, "name");
+    });
+}
@@ -0,0 +1,240 @@
+use ghost_gc::{
+    locked::LockedRefCell, Arena, Collect, Collector, CursorPhase, Finalize, Gc, Mutation, Pacing,
+    Rootable,
+};
+
+type Log<'b> = Gc<'b, LockedRefCell<Vec<Gc<'b, str>>>>;
+
+struct Root<'b> {
+    log: Log<'b>,
+}
+
+unsafe impl Collect for Root<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.log.trace(c);
+    }
+}
+
+impl Rootable for Root<'static> {
+    type Root<'l> = Root<'l>;
+}
+
+struct Resource<'b> {
+    name: &'static str,
+    log: Log<'b>,
+}
+
+unsafe impl Collect for Resource<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.log.trace(c);
+    }
+}
+
+impl<'b> Finalize<'b> for Resource<'b> {
+    fn finalize(&self, mt: &Mutation<'b>) {
+        let entry = Gc::from_str(self.name, mt);
+        self.log.write().unlock().borrow_mut().push(entry);
+    }
+}
+
+fn new_arena() -> Arena<Root<'static>> {
+    Arena::new(|mt| Root {
+        log: Gc::new(LockedRefCell::new(Vec::new()), mt),
+    })
+}
+
+#[test]
+fn finalizer_allocates_into_arena() {
+    let mut a = new_arena();
+
+    a.view(|root, mt| {
+        let _ = Gc::new_finalized(
+            Resource {
+                name: "resource",
+                log: root.log,
+            },
+            mt,
+        );
+    });
+
+    // The resource is unreachable, but is kept alive until its finalizer has run.
+    a.complete_collection();
+    assert_eq!(a.allocations(), 2);
+    a.view(|root, _| assert!(root.log.borrow().is_empty()));
+
+    a.run_finalizers();
+    a.view(|root, _| {
+        let log = root.log.borrow();
+        assert_eq!(log.len(), 1);
+        assert_eq!(&*log[0], "resource");
+    });
+
+    // The finalized resource is freed, and isn't finalized again.
+    a.complete_collection();
+    assert_eq!(a.allocations(), 2);
+
+    a.run_finalizers();
+    a.view(|root, _| assert_eq!(root.log.borrow().len(), 1));
+}
+
+#[test]
+fn drain_finalizers_in_batches() {
+    let mut a = new_arena();
+
+    a.view(|root, mt| {
+        for _ in 0..100 {
+            let _ = Gc::new_finalized(
+                Resource {
+                    name: "batch",
+                    log: root.log,
+                },
+                mt,
+            );
+        }
+    });
+
+    assert_eq!(a.pending_finalizers(), 0);
+    a.complete_collection();
+    assert_eq!(a.pending_finalizers(), 100);
+
+    let mut batches = 0;
+    while a.pending_finalizers() > 0 {
+        let ran = a.drain_finalizers(30);
+        assert!(ran <= 30);
+        batches += 1;
+    }
+
+    assert_eq!(batches, 4);
+    assert_eq!(a.drain_finalizers(30), 0);
+    a.view(|root, _| assert_eq!(root.log.borrow().len(), 100));
+}
+
+thread_local! {
+    static READ_AT_TEARDOWN: std::cell::RefCell<Vec<String>> = const {
+        std::cell::RefCell::new(Vec::new())
+    };
+}
+
+struct Reader<'b> {
+    sibling: Gc<'b, String>,
+}
+
+unsafe impl Collect for Reader<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.sibling.trace(c);
+    }
+}
+
+impl<'b> Finalize<'b> for Reader<'b> {
+    fn finalize(&self, _mt: &Mutation<'b>) {
+        READ_AT_TEARDOWN.with_borrow_mut(|read| read.push((*self.sibling).clone()));
+    }
+}
+
+#[test]
+fn finalizers_run_before_teardown_drops() {
+    let mut a = new_arena();
+
+    a.view(|_, mt| {
+        let queued = Gc::new(String::from("queued"), mt);
+        let _ = Gc::new_finalized(Reader { sibling: queued }, mt);
+    });
+    a.complete_collection();
+    assert_eq!(a.pending_finalizers(), 1);
+
+    a.view(|root, mt| {
+        let unreachable = Gc::new(String::from("unreachable"), mt);
+        let _ = Gc::new_finalized(
+            Reader {
+                sibling: unreachable,
+            },
+            mt,
+        );
+
+        // Reachable objects are finalized too, once the arena is dropped.
+        let reachable = Gc::new(String::from("reachable"), mt);
+        let reader = Gc::new_finalized(Reader { sibling: reachable }, mt);
+        let name = Gc::from_str(&reader.sibling, mt);
+        root.log.write().unlock().borrow_mut().push(name);
+    });
+
+    drop(a);
+
+    let mut read = READ_AT_TEARDOWN.take();
+    read.sort();
+    assert_eq!(read, ["queued", "reachable", "unreachable"]);
+}
+
+#[test]
+fn unreachable_objects_lists_finalizable_garbage() {
+    let mut a = new_arena();
+
+    let resource = a.view(|root, mt| {
+        let resource = Gc::new_finalized(
+            Resource {
+                name: "garbage",
+                log: root.log,
+            },
+            mt,
+        );
+        resource.as_ptr().cast_const().cast::<()>()
+    });
+
+    // Listing the garbage doesn't queue its finalizer.
+    let unreachable = a.unreachable_objects();
+    assert_eq!(unreachable.len(), 1);
+    assert_eq!(unreachable[0].0, resource);
+    assert_eq!(a.pending_finalizers(), 0);
+
+    a.complete_collection();
+    assert_eq!(a.pending_finalizers(), 1);
+}
+
+struct LogRoot;
+
+impl Rootable for LogRoot {
+    type Root<'l> = Log<'l>;
+}
+
+#[test]
+fn rollback_frees_finalizable_allocations() {
+    let pacing = Pacing {
+        trigger_allocations: Some(0),
+        mark_stride: 1,
+        ..Pacing::default()
+    };
+    let mut a =
+        Arena::<LogRoot>::new_paced(|mt| Gc::new(LockedRefCell::new(Vec::new()), mt), pacing);
+    let checkpoint = a.checkpoint();
+
+    a.view(|log, mt| {
+        for name in ["first", "second"] {
+            let _ = Gc::new_finalized(Resource { name, log: *log }, mt);
+        }
+    });
+
+    // Queue the finalizers, without sweeping anything.
+    while a.pending_finalizers() == 0 {
+        a.run_collection();
+    }
+    assert_eq!(a.pending_finalizers(), 2);
+    assert!(matches!(
+        a.save_collection_cursor().phase,
+        CursorPhase::Mark { .. }
+    ));
+
+    a.rollback(checkpoint);
+    assert_eq!(a.allocations(), 1);
+    assert_eq!(a.pending_finalizers(), 0);
+
+    // The freed objects are forgotten, rather than finalized.
+    a.complete_collection();
+    assert_eq!(a.pending_finalizers(), 0);
+    a.view(|log, _| assert!(log.borrow().is_empty()));
+}
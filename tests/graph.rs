@@ -32,7 +32,7 @@ impl<'b, T> Graph<'b, T> {
 }
 
 unsafe impl<T: Collect> Collect for Graph<'_, T> {
-    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+    const NEEDS_TRACE: bool = true;
 
     fn trace(&self, c: &ghost_gc::Collector) {
         self.0.trace(c);
@@ -50,7 +50,7 @@ struct Node<'b, T> {
 }
 
 unsafe impl<T: Collect> Collect for Node<'_, T> {
-    const NEEDS_TRACE: bool = T::NEEDS_TRACE;
+    const NEEDS_TRACE: bool = true;
 
     fn trace(&self, c: &ghost_gc::Collector) {
         self.value.trace(c);
@@ -80,3 +80,161 @@ fn basic() {
     a.complete_collection();
     assert_eq!(a.allocations(), 3);
 }
+
+#[test]
+#[cfg(debug_assertions)]
+fn trace_report_visits_all_nodes() {
+    let mut a = Arena::<Graph<'_, i32>>::new(|_| Graph(vec![]));
+
+    let mut nodes = a.view_mut(|graph, mt| {
+        graph.add_node(0, None, mt);
+        graph.add_node(1, Some(0), mt);
+        graph.add_node(2, Some(1), mt);
+
+        // Only keep the last node in the root, so the rest are only reachable through parents.
+        let last = graph.0[2];
+        let nodes: Vec<_> = graph
+            .0
+            .iter()
+            .map(|n| n.as_ptr().cast_const().cast())
+            .collect();
+        graph.0 = vec![last];
+
+        nodes
+    });
+
+    let mut report = a.trace_report();
+
+    nodes.sort();
+    report.sort();
+    assert_eq!(report, nodes);
+}
+
+struct NodeSlice;
+
+impl Rootable for NodeSlice {
+    type Root<'l> = Gc<'l, [Gc<'l, Node<'l, i32>>]>;
+}
+
+#[test]
+fn slice_of_nodes_marks_every_element() {
+    let mut a = Arena::<NodeSlice>::new(|mt| {
+        let root = Gc::new(
+            Node {
+                value: -1,
+                parent: LockedCell::new(None),
+            },
+            mt,
+        );
+        let nodes: Vec<_> = (0..16)
+            .map(|i| {
+                let _garbage = Gc::new(i, mt);
+                Gc::new(
+                    Node {
+                        value: i,
+                        parent: LockedCell::new(Some(root)),
+                    },
+                    mt,
+                )
+            })
+            .collect();
+
+        UniqueGc::into_gc(UniqueGc::from_slice(&nodes, mt))
+    });
+
+    // The slice, its 16 elements, their shared parent, and 16 unreachable integers.
+    assert_eq!(a.allocations(), 34);
+    a.complete_collection();
+    assert_eq!(a.allocations(), 18);
+
+    a.view(|nodes, _| {
+        assert_eq!(nodes.len(), 16);
+        for (i, node) in nodes.iter().enumerate() {
+            assert_eq!(node.value, i as i32);
+            assert_eq!(node.parent.get().unwrap().value, -1);
+        }
+    });
+}
+
+#[test]
+fn alloc_graph_cycle() {
+    let mut a = Arena::<Graph<'_, i32>>::new(|mt| {
+        let nodes = mt.alloc_graph(
+            (0..3).map(|value| Node {
+                value,
+                parent: LockedCell::new(None),
+            }),
+            |nodes| {
+                for (i, node) in nodes.iter().enumerate() {
+                    let next = nodes[(i + 1) % nodes.len()];
+                    node.write().project(|n| &n.parent).unlock().set(Some(next));
+                }
+            },
+        );
+
+        // Only one node is rooted, the others are only reachable around the cycle.
+        Graph(vec![nodes[0]])
+    });
+
+    a.complete_collection();
+    assert_eq!(a.allocations(), 3);
+
+    a.view(|graph, _| {
+        let mut node = graph.0[0];
+        for expected in [1, 2, 0] {
+            node = node.parent.get().unwrap();
+            assert_eq!(node.value, expected);
+        }
+        assert!(Gc::ptr_eq(node, graph.0[0]));
+    });
+}
+
+struct RootNode;
+
+impl Rootable for RootNode {
+    type Root<'l> = Gc<'l, Node<'l, i32>>;
+}
+
+#[test]
+fn gc_root_keeps_ancestors_alive() {
+    let mut a = Arena::<RootNode>::new(|mt| {
+        let grandparent = Gc::new(
+            Node {
+                value: 0,
+                parent: LockedCell::new(None),
+            },
+            mt,
+        );
+        let parent = Gc::new(
+            Node {
+                value: 1,
+                parent: LockedCell::new(Some(grandparent)),
+            },
+            mt,
+        );
+        let _garbage = Gc::new(100, mt);
+
+        Gc::new(
+            Node {
+                value: 2,
+                parent: LockedCell::new(Some(parent)),
+            },
+            mt,
+        )
+    });
+
+    assert_eq!(a.allocations(), 4);
+    a.complete_collection();
+    assert_eq!(a.allocations(), 3);
+
+    a.view(|node, _| {
+        let parent = node.parent.get().unwrap();
+        assert_eq!(parent.value, 1);
+        assert_eq!(parent.parent.get().unwrap().value, 0);
+    });
+
+    // Cutting the chain below the root frees both ancestors.
+    a.view(|node, _| node.write().project(|n| &n.parent).unlock().set(None));
+    a.complete_collection();
+    assert_eq!(a.allocations(), 1);
+}
@@ -0,0 +1,73 @@
+use ghost_gc::{locked::LockedCell, Collect, Collector, Gc, GcHeap};
+
+struct Node<'b> {
+    value: u32,
+    next: LockedCell<Option<Gc<'b, Node<'b>>>>,
+}
+
+unsafe impl Collect for Node<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.next.trace(c);
+    }
+}
+
+/// Runtime state, which stores its roots alongside everything else.
+struct Vm<'h> {
+    stack: Vec<Gc<'h, Node<'h>>>,
+    steps: usize,
+}
+
+#[test]
+fn external_root() {
+    GcHeap::scope(|heap| {
+        let mut vm = Vm {
+            stack: Vec::new(),
+            steps: 0,
+        };
+
+        for value in 0..10 {
+            let mt = heap.mutation();
+            let next = vm.stack.last().copied();
+            let node = Gc::new(
+                Node {
+                    value,
+                    next: LockedCell::new(next),
+                },
+                mt,
+            );
+
+            // Each node is only kept alive through the node after it.
+            vm.stack.clear();
+            vm.stack.push(node);
+
+            let _garbage = Gc::new(
+                Node {
+                    value: 100,
+                    next: LockedCell::new(None),
+                },
+                mt,
+            );
+
+            vm.steps += 1;
+            unsafe { heap.run_collection(&vm.stack) };
+        }
+
+        unsafe { heap.complete_collection(&vm.stack) };
+        assert_eq!(heap.allocations(), 10);
+        assert_eq!(vm.steps, 10);
+
+        let mut values = Vec::new();
+        let mut node = vm.stack.last().copied();
+        while let Some(n) = node {
+            values.push(n.value);
+            node = n.next.get();
+        }
+        assert_eq!(values, (0..10).rev().collect::<Vec<_>>());
+
+        vm.stack.clear();
+        unsafe { heap.complete_collection(&vm.stack) };
+        assert_eq!(heap.allocations(), 0);
+    });
+}
@@ -0,0 +1,341 @@
+use std::{cell::RefCell, rc::Rc};
+
+use ghost_gc::{
+    locked::{LockedCell, LockedRefCell, WeakCell},
+    Arena, Collect, Collector, Gc, Pacing, Rootable, Weak,
+};
+
+#[derive(Default)]
+struct Root<'b> {
+    strong: LockedRefCell<Vec<Gc<'b, u32>>>,
+    weak: LockedRefCell<Vec<Weak<'b, u32>>>,
+}
+
+unsafe impl Collect for Root<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.strong.trace(c);
+        self.weak.trace(c);
+    }
+}
+
+impl Rootable for Root<'static> {
+    type Root<'l> = Root<'l>;
+}
+
+#[test]
+fn weak_only_storage_is_reclaimed() {
+    let mut a = Arena::<Root>::new(|_| Root::default());
+
+    for round in 0..50 {
+        a.view_mut(|root, mt| {
+            let strong = Gc::new(round, mt);
+            let weak_only = Gc::new(round, mt);
+
+            // Strongly reachable objects with weak references must stay alive.
+            root.strong.get_mut().push(strong);
+            root.weak.get_mut().push(Gc::downgrade(strong));
+            root.weak.get_mut().push(Gc::downgrade(weak_only));
+        });
+
+        a.complete_collection();
+
+        a.view(|root, _| {
+            let weak = root.weak.borrow();
+            let last = &weak[weak.len() - 2..];
+            assert_eq!(last[0].upgrade().map(|gc| *gc), Some(round));
+            assert_eq!(last[1].upgrade(), None);
+        });
+
+        // Dropping the weak handles allows the dead objects' storage to be freed.
+        if round % 10 == 9 {
+            a.view_mut(|root, _| root.weak.get_mut().clear());
+            a.complete_collection();
+            assert_eq!(a.allocations(), round as usize + 1);
+        }
+    }
+
+    a.view_mut(|root, _| root.strong.get_mut().clear());
+    a.complete_collection();
+    assert_eq!(a.allocations(), 0);
+}
+
+#[test]
+fn weak_cleared_callback_reports_address() {
+    let cleared = Rc::new(RefCell::new(Vec::new()));
+    let mut a = Arena::<Root>::new(|_| Root::default());
+
+    let sink = cleared.clone();
+    a.on_weak_cleared(move |addr| sink.borrow_mut().push(addr as usize));
+
+    let (kept, dropped) = a.view_mut(|root, mt| {
+        let kept = Gc::new(1, mt);
+        let dropped = Gc::new(2, mt);
+        root.strong.get_mut().push(kept);
+        root.weak.get_mut().push(Gc::downgrade(kept));
+        root.weak.get_mut().push(Gc::downgrade(dropped));
+        (kept.as_ptr() as usize, dropped.as_ptr() as usize)
+    });
+
+    a.complete_collection();
+    assert_eq!(*cleared.borrow(), [dropped]);
+    assert_ne!(dropped, kept);
+
+    // An object's value is only dropped once, so later cycles don't report it again.
+    a.complete_collection();
+    assert_eq!(*cleared.borrow(), [dropped]);
+}
+
+#[derive(Default)]
+struct Slots<'b> {
+    value: u32,
+    left: LockedCell<Option<Gc<'b, Slots<'b>>>>,
+    right: LockedCell<Option<Gc<'b, Slots<'b>>>>,
+    parent: Weak<'b, Slots<'b>>,
+}
+
+unsafe impl Collect for Slots<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.left.trace(c);
+        self.right.trace(c);
+        self.parent.trace(c);
+    }
+}
+
+impl Rootable for Slots<'static> {
+    type Root<'l> = Gc<'l, Slots<'l>>;
+}
+
+#[test]
+fn default_slots_are_empty() {
+    let mut a = Arena::<Slots>::new(|mt| Gc::new(Slots::default(), mt));
+    a.complete_collection();
+
+    a.view(|root, mt| {
+        assert_eq!(root.value, 0);
+        assert!(root.left.get().is_none());
+        assert!(root.right.get().is_none());
+        assert!(root.parent.upgrade().is_none());
+        assert!(Weak::<u32>::new().upgrade().is_none());
+
+        let child = Gc::new(
+            Slots {
+                value: 1,
+                parent: Gc::downgrade(*root),
+                ..Default::default()
+            },
+            mt,
+        );
+        root.write().project(|s| &s.left).unlock().set(Some(child));
+    });
+
+    a.complete_collection();
+    assert_eq!(a.allocations(), 2);
+
+    a.view(|root, _| {
+        let child = root.left.get().unwrap();
+        assert_eq!(child.value, 1);
+        assert!(child.left.get().is_none());
+        assert!(Gc::ptr_eq(child.parent.upgrade().unwrap(), *root));
+    });
+}
+
+thread_local! {
+    static UPGRADED_IN_DROP: std::cell::RefCell<Vec<(u32, Option<u32>)>> = const {
+        std::cell::RefCell::new(Vec::new())
+    };
+}
+
+struct Ring<'b> {
+    id: u32,
+    next: LockedCell<Weak<'b, Ring<'b>>>,
+}
+
+unsafe impl Collect for Ring<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.next.trace(c);
+    }
+}
+
+impl Drop for Ring<'_> {
+    fn drop(&mut self) {
+        let next = self.next.get().upgrade().map(|next| next.id);
+        UPGRADED_IN_DROP.with_borrow_mut(|log| log.push((self.id, next)));
+    }
+}
+
+struct RingRoot;
+
+impl Rootable for RingRoot {
+    type Root<'l> = Vec<Gc<'l, Ring<'l>>>;
+}
+
+/// Should also be run under Miri, which catches an upgrade to an object whose storage is freed.
+#[test]
+fn destructors_upgrade_siblings_at_teardown() {
+    let a = Arena::<RingRoot>::new(|mt| {
+        let ring: Vec<_> = (0..8)
+            .map(|id| {
+                let next = LockedCell::new(Weak::new());
+                Gc::new(Ring { id, next }, mt)
+            })
+            .collect();
+
+        for (i, node) in ring.iter().enumerate() {
+            let next = Gc::downgrade(ring[(i + 1) % ring.len()]);
+            node.write().project(|n| &n.next).unlock().set(next);
+        }
+
+        ring
+    });
+
+    drop(a);
+
+    let log = UPGRADED_IN_DROP.take();
+    assert_eq!(log.len(), 8);
+    for (id, next) in &log {
+        assert!(next.is_none_or(|next| next == (id + 1) % 8));
+    }
+
+    // At least one node must have been dropped before the one pointing to it.
+    assert!(log.iter().any(|(_, next)| next.is_none()));
+}
+
+struct Child<'b> {
+    parent: WeakCell<'b, u32>,
+    observer: Option<Weak<'b, u32>>,
+}
+
+unsafe impl Collect for Child<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.parent.trace(c);
+        self.observer.trace(c);
+    }
+}
+
+struct FamilyRoot<'b> {
+    parents: LockedRefCell<Vec<Gc<'b, u32>>>,
+    child: Gc<'b, Child<'b>>,
+}
+
+unsafe impl Collect for FamilyRoot<'_> {
+    const NEEDS_TRACE: bool = true;
+
+    fn trace(&self, c: &Collector) {
+        self.parents.trace(c);
+        self.child.trace(c);
+    }
+}
+
+impl Rootable for FamilyRoot<'static> {
+    type Root<'l> = FamilyRoot<'l>;
+}
+
+#[test]
+fn weak_back_edge_set_during_mark() {
+    const { assert!(<Option<Weak<u32>> as Collect>::NEEDS_TRACE) };
+
+    let pacing = Pacing {
+        trigger_allocations: Some(0),
+        mark_stride: 1,
+        ..Pacing::default()
+    };
+    let mut a = Arena::<FamilyRoot>::new_paced(
+        |mt| {
+            let observed = Gc::new(0, mt);
+            FamilyRoot {
+                parents: LockedRefCell::new(vec![observed]),
+                child: Gc::new(
+                    Child {
+                        parent: WeakCell::new(),
+                        observer: Some(Gc::downgrade(observed)),
+                    },
+                    mt,
+                ),
+            }
+        },
+        pacing,
+    );
+
+    // Starts marking, then marks the child before the parents.
+    a.run_collection();
+    a.run_collection();
+
+    a.view_mut(|root, mt| {
+        let parent = Gc::new(1, mt);
+        root.parents.get_mut().push(parent);
+        root.child.write().project(|c| &c.parent).set(Some(parent));
+    });
+
+    while a.collect_generation_stats().completed_cycles == 0 {
+        a.run_collection();
+    }
+
+    a.view(|root, _| {
+        assert_eq!(root.child.parent.upgrade().as_deref(), Some(&1));
+        assert_eq!(
+            root.child.observer.and_then(Weak::upgrade).as_deref(),
+            Some(&0)
+        );
+    });
+
+    a.view_mut(|root, _| root.parents.get_mut().clear());
+    a.complete_collection();
+    a.view(|root, _| {
+        assert_eq!(root.child.parent.upgrade(), None);
+        assert_eq!(root.child.observer.and_then(Weak::upgrade), None);
+    });
+}
+
+/// A container which is only ever visited for its weak reference.
+struct WeakOnly<'b> {
+    target: Weak<'b, u32>,
+}
+
+unsafe impl Collect for WeakOnly<'_> {
+    const NEEDS_TRACE: bool = <Weak<u32> as Collect>::NEEDS_TRACE;
+
+    fn trace(&self, c: &Collector) {
+        self.target.trace(c);
+    }
+}
+
+struct WeakOnlyRoot;
+
+impl Rootable for WeakOnlyRoot {
+    type Root<'l> = (Gc<'l, WeakOnly<'l>>, Option<Gc<'l, u32>>);
+}
+
+#[test]
+fn weak_only_container_does_not_retain_target() {
+    // The container has to be visited for the weak colour to be applied.
+    const { assert!(<WeakOnly as Collect>::NEEDS_TRACE) };
+
+    let mut a = Arena::<WeakOnlyRoot>::new(|mt| {
+        let target = Gc::new(7, mt);
+        let container = Gc::new(
+            WeakOnly {
+                target: Gc::downgrade(target),
+            },
+            mt,
+        );
+        (container, Some(target))
+    });
+
+    a.complete_collection();
+    a.view(|(container, _), _| assert_eq!(container.target.upgrade().as_deref(), Some(&7)));
+
+    a.view_mut(|(_, target), _| *target = None);
+    a.complete_collection();
+
+    // The target's value is gone, and only its storage is kept for the weak reference.
+    a.view(|(container, _), _| assert_eq!(container.target.upgrade(), None));
+    assert_eq!(a.allocations(), 2);
+}